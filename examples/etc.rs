@@ -28,7 +28,7 @@ pub fn make_spellball_wop() {
     spellball.set_data(0, -1);
     spellball.set_data(1, -1);
     spellball.set_object_type_collision(ObjectTypeCollision::NONE);
-    spellball.to_wop("Spellball").unwrap();
+    spellball.to_file("Spellball.wop").unwrap();
 
     println!("{:#?}", spellball);
 }
@@ -40,7 +40,7 @@ pub fn make_mechanism_piece_wop() {
     obj.set_movement_type(MovementType::NORTH_LEFT);
     obj.set_movement_speed(1000);
     obj.set_button_push(1);
-    obj.to_wop("Mechanism Piece").unwrap();
+    obj.to_file("Mechanism Piece.wop").unwrap();
 }
 
 pub fn make_item_maze_check_dia() {
@@ -56,7 +56,7 @@ pub fn make_item_maze_check_dia() {
         vec![Reply::continue_to(1, String::from("What?"), Cmd::none())]
     );
     dialog.add_item_consumption_sequence(22, interchange_item_check, interchange_item_missing);
-    dialog.to_dia("1").unwrap();
+    dialog.to_file("1.dia").unwrap();
 }
 
 pub fn make_funny_password_dia() {
@@ -66,5 +66,14 @@ pub fn make_funny_password_dia() {
         Body::one_liner(String::from("...")),
         &["1", "2", "3", "4", "5", "6"],
         &[2, 1, 0, 1, 2]);
-    dialog.to_dia("1").unwrap();
-}
\ No newline at end of file
+    dialog.to_file("1.dia").unwrap();
+}
+
+pub fn make_shop_dia() {
+    let mut dialog = Dialog::default();
+    dialog.add_shop(&[
+        ShopItem::new(String::from("a Gloop"), 5, Cmd::activate(100)),
+        ShopItem::new(String::from("a Spring"), 12, Cmd::activate(101)),
+    ], &ShopTemplate::default());
+    dialog.to_file("1.dia").unwrap();
+}
@@ -0,0 +1,250 @@
+use std::{fs, io, panic::{self, AssertUnwindSafe}, path::{Path, PathBuf}, sync::{atomic::{AtomicUsize, Ordering}, Mutex}, thread, time::Instant};
+
+use crate::*;
+use serde::Serialize;
+
+/// The kinds of file that can be converted, going by extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    Level,
+    Dialog,
+    Object,
+}
+
+impl FileKind {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "wlv" => Some(FileKind::Level),
+            "dia" => Some(FileKind::Dialog),
+            "wop" => Some(FileKind::Object),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchOptions {
+    pub recursive: bool,
+    /// Level format to write. By default OpenWA levels stay OpenWA and everything else becomes vanilla.
+    pub format: Option<WlvFormat>,
+    /// Do everything except writing.
+    pub dry_run: bool,
+    /// How many files to convert at once. Zero uses every core.
+    pub threads: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    Converted,
+    /// Not a kind of file that gets converted.
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub kind: Option<FileKind>,
+    pub status: FileStatus,
+    /// What was done, or why it failed.
+    pub message: String,
+    pub warnings: Vec<String>,
+    pub milliseconds: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BatchReport {
+    pub converted: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub milliseconds: f64,
+    /// In the order the files were found, no matter which finished first.
+    pub files: Vec<FileReport>,
+}
+
+impl BatchReport {
+    pub fn is_success(&self) -> bool {
+        self.failed == 0
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("batch reports should always serialize")
+    }
+}
+
+/// Converts a file or every file in a directory, spread across threads.
+/// Failures, including panics, are recorded in the report and don't stop the other files.
+/// `progress` is called from the worker threads as each file finishes.
+pub fn convert_batch<F>(input: &Path, output: &Path, options: &BatchOptions, progress: F) -> io::Result<BatchReport>
+where
+    F: Fn(&FileReport) + Sync
+{
+    let start = Instant::now();
+    let jobs = if input.is_dir() {
+        let mut files = vec![];
+        collect_files(input, options.recursive, &mut files)?;
+        files.into_iter()
+            .map(|file| {
+                let relative = file.strip_prefix(input).expect("collected files should be inside the input").to_owned();
+                (file, output.join(relative))
+            })
+            .collect()
+    } else if output.is_dir() {
+        vec![(input.to_owned(), output.join(input.file_name().unwrap_or_default()))]
+    } else {
+        vec![(input.to_owned(), output.to_owned())]
+    };
+
+    let threads = match options.threads {
+        0 => thread::available_parallelism().map_or(1, |threads| threads.get()),
+        threads => threads
+    }.min(jobs.len()).max(1);
+    let next_job = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<FileReport>>> = Mutex::new(vec![None; jobs.len()]);
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let index = next_job.fetch_add(1, Ordering::Relaxed);
+                let Some((input, output)) = jobs.get(index) else { break };
+                let report = convert_file_reported(input, output, options);
+                progress(&report);
+                results.lock().expect("no worker should panic while holding the lock")[index] = Some(report);
+            });
+        }
+    });
+
+    let mut report = BatchReport::default();
+    for file in results.into_inner().expect("no worker should panic while holding the lock") {
+        let file = file.expect("every job should have been run");
+        match file.status {
+            FileStatus::Converted => report.converted += 1,
+            FileStatus::Skipped => report.skipped += 1,
+            FileStatus::Failed => report.failed += 1
+        }
+        report.files.push(file);
+    }
+    report.milliseconds = start.elapsed().as_secs_f64() * 1000.0;
+    Ok(report)
+}
+
+fn collect_files(directory: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = fs::read_dir(directory)?.map(|entry| entry.map(|entry| entry.path())).collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            if recursive {
+                collect_files(&path, recursive, files)?;
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn convert_file_reported(input: &Path, output: &Path, options: &BatchOptions) -> FileReport {
+    let start = Instant::now();
+    let kind = FileKind::from_path(input);
+    let mut warnings = vec![];
+    let result = match kind {
+        None => Ok(String::from("not a .wlv, .dia or .wop file")),
+        Some(kind) => panic::catch_unwind(AssertUnwindSafe(|| convert_file(kind, input, output, options, &mut warnings)))
+            .unwrap_or_else(|panic| {
+                let message = panic.downcast_ref::<&str>().map(|message| message.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                Err(format!("panicked: {}", message))
+            })
+    };
+    let (status, message) = match (kind, result) {
+        (None, Ok(message)) => (FileStatus::Skipped, message),
+        (Some(_), Ok(message)) => (FileStatus::Converted, message),
+        (_, Err(message)) => (FileStatus::Failed, message)
+    };
+    FileReport {
+        input: input.to_owned(),
+        output: output.to_owned(),
+        kind,
+        status,
+        message,
+        warnings,
+        milliseconds: start.elapsed().as_secs_f64() * 1000.0,
+    }
+}
+
+// Returns a note on what was done.
+fn convert_file(kind: FileKind, input: &Path, output: &Path, options: &BatchOptions, warnings: &mut Vec<String>) -> Result<String, String> {
+    let mut bytes = vec![];
+    let note = match kind {
+        FileKind::Level => {
+            let (wlv, detection) = AnyWlv::from_file(input).map_err(|error| error.to_string())?;
+            let level = match wlv {
+                AnyWlv::Beta(beta) => {
+                    let (level, report) = beta.convert(&ConversionOptions::default());
+                    let guessed_tiles = report.decisions.iter().filter(|decision| matches!(decision, ConversionDecision::TileLogic { .. })).count();
+                    if guessed_tiles > 0 {
+                        warnings.push(format!("guessed the logic of {} tiles", guessed_tiles));
+                    }
+                    warnings.extend(report.decisions.iter()
+                        .filter(|decision| !matches!(decision, ConversionDecision::TileLogic { .. }))
+                        .map(|decision| decision.to_string()));
+                    level
+                },
+                AnyWlv::Latest(level) => level
+            };
+            let format = options.format.unwrap_or(if level.open_wa().is_some() { WlvFormat::OpenWa } else { WlvFormat::Vanilla });
+            bytes = level.to_bytes(format).map_err(|error| error.to_string())?;
+            format!("{} level written as {}", detection.version, format)
+        },
+        FileKind::Dialog => {
+            let dialog = Dialog::from_dia(input).map_err(|error| error.to_string())?;
+            serde_blitz3d::to_writer(&mut bytes, &dialog).map_err(|error| error.to_string())?;
+            String::from("dialog")
+        },
+        FileKind::Object => {
+            let object = GameObject::from_wop(input).map_err(|error| error.to_string())?;
+            serde_blitz3d::to_writer(&mut bytes, &object).map_err(|error| error.to_string())?;
+            String::from("object")
+        }
+    };
+    if options.dry_run {
+        return Ok(format!("{}, {} bytes not written", note, bytes.len()));
+    }
+    if let Some(parent) = output.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|error| format!("{}: {}", parent.display(), error))?;
+    }
+    fs::write(output, &bytes).map_err(|error| format!("{}: {}", output.display(), error))?;
+    Ok(note)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_keeps_going_past_failures() {
+        let root = std::env::temp_dir().join(format!("waust-batch-{}", std::process::id()));
+        let (input, output) = (root.join("in"), root.join("out"));
+        fs::create_dir_all(input.join("nested")).unwrap();
+        Level::new(2, 2).to_file(input.join("good.wlv")).unwrap();
+        WlvBeta::new(2, 2).to_file(input.join("nested").join("beta.wlv")).unwrap();
+        fs::write(input.join("bad.wlv"), b"not a level").unwrap();
+        fs::write(input.join("notes.txt"), b"").unwrap();
+
+        let options = BatchOptions {
+            recursive: true,
+            threads: 2,
+            ..Default::default()
+        };
+        let report = convert_batch(&input, &output, &options, |_| {}).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!((report.converted, report.skipped, report.failed), (2, 1, 1));
+        assert_eq!(report.files[0].input, input.join("bad.wlv"));
+        assert_eq!(report.files[0].status, FileStatus::Failed);
+        assert!(report.to_json().contains("\"status\": \"failed\""));
+    }
+}
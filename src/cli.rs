@@ -0,0 +1,570 @@
+// The waust command-line tool. Arguments are parsed by hand to keep dependencies down.
+
+use std::{fs, path::{Path, PathBuf}, process::ExitCode};
+
+use crate::*;
+
+pub const EXIT_SUCCESS: u8 = 0;
+/// At least one file couldn't be processed or didn't pass validation.
+pub const EXIT_FAILURE: u8 = 1;
+/// The arguments didn't make sense.
+pub const EXIT_USAGE: u8 = 2;
+
+const USAGE: &str = "\
+usage: waust <command> [options]
+
+commands:
+  convert <input> <output>    Convert levels to the latest format and rewrite dialogs and objects.
+                              Directories are converted file by file.
+      --format vanilla|openwa   Level format to write. By default OpenWA levels stay OpenWA.
+      --recursive               Descend into subdirectories.
+      --jobs <n>                How many files to convert at once. By default one per core.
+      --report <file>           Write a JSON report of every file converted. A dry run prints it instead.
+  info <file>...              Summarize levels, dialogs and objects.
+  dump <file>...              Print everything in levels, dialogs and objects.
+  validate <file>...          Check that files parse strictly and look sound.
+  export <dialog> <output>    Export a dialog for translation or proofreading.
+      --format po|csv|md|html   By default chosen by the output's extension.
+      --dialog-number <n>       Number used in translation keys. By default taken from the file name.
+  import <dialog> <translations> <output>
+                              Apply a PO or CSV translation to a dialog.
+      --dialog-number <n>
+  to-text <input> <output>    Write a level, dialog or object as editable text.
+      --format json|ron         By default chosen by the output's extension, or JSON.
+  to-binary <input> <output>  Turn text written by to-text back into a level, dialog or object.
+      --format json|ron         By default chosen by the input's extension, or JSON.
+
+options for every command that writes files:
+      --dry-run                 Do everything except writing.
+";
+
+/// Runs the tool with the arguments that follow the program name.
+pub fn run<I>(args: I) -> ExitCode
+where
+    I: IntoIterator<Item = String>
+{
+    let args: Vec<String> = args.into_iter().collect();
+    ExitCode::from(exit_code(&args))
+}
+
+/// Runs the tool and returns what it should exit with.
+pub fn exit_code(args: &[String]) -> u8 {
+    match Invocation::parse(args) {
+        Ok(Invocation::Help) => {
+            print!("{}", USAGE);
+            EXIT_SUCCESS
+        },
+        Ok(invocation) => if invocation.execute() { EXIT_SUCCESS } else { EXIT_FAILURE },
+        Err(usage) => {
+            eprintln!("error: {}\n\n{}", usage, USAGE);
+            EXIT_USAGE
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Po,
+    Csv,
+    Markdown,
+    Html,
+}
+
+/// A command with its arguments checked, ready to run.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Invocation {
+    Help,
+    Convert { input: PathBuf, output: PathBuf, options: BatchOptions, report: Option<PathBuf> },
+    Info(Vec<PathBuf>),
+    Dump(Vec<PathBuf>),
+    Validate(Vec<PathBuf>),
+    Export { dialog: PathBuf, output: PathBuf, format: ExportFormat, dialog_number: i32, dry_run: bool },
+    Import { dialog: PathBuf, translations: PathBuf, output: PathBuf, dialog_number: i32, dry_run: bool },
+    ToText { input: PathBuf, output: PathBuf, format: TextFormat, dry_run: bool },
+    ToBinary { input: PathBuf, output: PathBuf, format: TextFormat, dry_run: bool },
+}
+
+impl Invocation {
+    /// Checks the arguments without touching any files. The error says what's wrong with them.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+            return Ok(Invocation::Help);
+        }
+        let (command, args) = args.split_first().ok_or_else(|| String::from("expected a command"))?;
+        match command.as_str() {
+            "convert" => {
+                let args = Args::parse(args, &[DRY_RUN, FORMAT, ("--recursive", false), ("--jobs", true), ("--report", true)])?;
+                let [input, output] = args.positional(["input", "output"])?;
+                let options = BatchOptions {
+                    recursive: args.has("--recursive"),
+                    format: match args.value(FORMAT.0) {
+                        None => None,
+                        Some("vanilla") => Some(WlvFormat::Vanilla),
+                        Some("openwa") => Some(WlvFormat::OpenWa),
+                        Some(other) => return Err(format!("unknown level format {:?}", other))
+                    },
+                    dry_run: args.has(DRY_RUN.0),
+                    threads: match args.value("--jobs") {
+                        Some(jobs) => jobs.parse().map_err(|_| format!("invalid number of jobs {:?}", jobs))?,
+                        None => 0
+                    },
+                };
+                Ok(Invocation::Convert { input: input.into(), output: output.into(), options, report: args.value("--report").map(PathBuf::from) })
+            },
+            "info" => Ok(Invocation::Info(Args::parse(args, &[])?.files()?)),
+            "dump" => Ok(Invocation::Dump(Args::parse(args, &[])?.files()?)),
+            "validate" => Ok(Invocation::Validate(Args::parse(args, &[])?.files()?)),
+            "export" => {
+                let args = Args::parse(args, &[DRY_RUN, DIALOG_NUMBER, FORMAT])?;
+                let [dialog, output] = args.positional(["dialog", "output"])?;
+                let format = match args.value(FORMAT.0) {
+                    Some(format) => format.to_owned(),
+                    None => lowercase_extension(Path::new(output))
+                };
+                let format = match format.as_str() {
+                    "po" => ExportFormat::Po,
+                    "csv" => ExportFormat::Csv,
+                    "md" => ExportFormat::Markdown,
+                    "html" | "htm" => ExportFormat::Html,
+                    _ => return Err(format!("unknown export format {:?}", format))
+                };
+                let dialog_number = args.dialog_number(Path::new(dialog))?;
+                Ok(Invocation::Export { dialog: dialog.into(), output: output.into(), format, dialog_number, dry_run: args.has(DRY_RUN.0) })
+            },
+            "import" => {
+                let args = Args::parse(args, &[DRY_RUN, DIALOG_NUMBER])?;
+                let [dialog, translations, output] = args.positional(["dialog", "translations", "output"])?;
+                let dialog_number = args.dialog_number(Path::new(dialog))?;
+                Ok(Invocation::Import { dialog: dialog.into(), translations: translations.into(), output: output.into(), dialog_number, dry_run: args.has(DRY_RUN.0) })
+            },
+            "to-text" => {
+                let args = Args::parse(args, &[DRY_RUN, FORMAT])?;
+                let [input, output] = args.positional(["input", "output"])?;
+                let format = args.text_format(Path::new(output))?;
+                Ok(Invocation::ToText { input: input.into(), output: output.into(), format, dry_run: args.has(DRY_RUN.0) })
+            },
+            "to-binary" => {
+                let args = Args::parse(args, &[DRY_RUN, FORMAT])?;
+                let [input, output] = args.positional(["input", "output"])?;
+                let format = args.text_format(Path::new(input))?;
+                Ok(Invocation::ToBinary { input: input.into(), output: output.into(), format, dry_run: args.has(DRY_RUN.0) })
+            },
+            other => Err(format!("unknown command {:?}", other))
+        }
+    }
+
+    /// Reports failures as it goes and returns whether everything succeeded.
+    pub fn execute(&self) -> bool {
+        match self {
+            Invocation::Help => {
+                print!("{}", USAGE);
+                true
+            },
+            Invocation::Convert { input, output, options, report } => convert(input, output, options, report.as_deref()),
+            Invocation::Info(files) => for_each_file(files, info),
+            Invocation::Dump(files) => for_each_file(files, dump),
+            Invocation::Validate(files) => for_each_file(files, validate),
+            Invocation::Export { dialog, output, format, dialog_number, dry_run } => export(dialog, output, *format, *dialog_number, *dry_run),
+            Invocation::Import { dialog, translations, output, dialog_number, dry_run } => import(dialog, translations, output, *dialog_number, *dry_run),
+            Invocation::ToText { input, output, format, dry_run } => to_text(input, output, *format, *dry_run),
+            Invocation::ToBinary { input, output, format, dry_run } => to_binary(input, output, *format, *dry_run)
+        }
+    }
+}
+
+// Positional arguments and flags, checked against what the command accepts.
+struct Args {
+    positional: Vec<String>,
+    flags: Vec<(&'static str, Option<String>)>,
+}
+
+impl Args {
+    // Each known flag is paired with whether it takes a value.
+    fn parse(args: &[String], known: &[(&'static str, bool)]) -> Result<Self, String> {
+        let mut parsed = Args {
+            positional: vec![],
+            flags: vec![],
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                parsed.positional.push(arg.clone());
+                continue;
+            }
+            let &(name, takes_value) = known.iter()
+                .find(|(name, _)| name == arg)
+                .ok_or_else(|| format!("unknown option {}", arg))?;
+            let value = if takes_value {
+                Some(args.next().ok_or_else(|| format!("{} needs a value", name))?.clone())
+            } else {
+                None
+            };
+            parsed.flags.push((name, value));
+        }
+        Ok(parsed)
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.flags.iter().any(|(flag, _)| *flag == name)
+    }
+
+    fn value(&self, name: &str) -> Option<&str> {
+        self.flags.iter().rev().find(|(flag, _)| *flag == name).and_then(|(_, value)| value.as_deref())
+    }
+
+    fn positional<const N: usize>(&self, names: [&str; N]) -> Result<[&str; N], String> {
+        if self.positional.len() != N {
+            return Err(format!("expected {} argument(s): {}", N, names.join(", ")));
+        }
+        Ok(std::array::from_fn(|i| self.positional[i].as_str()))
+    }
+
+    fn files(&self) -> Result<Vec<PathBuf>, String> {
+        if self.positional.is_empty() {
+            return Err(String::from("expected at least one file"));
+        }
+        Ok(self.positional.iter().map(PathBuf::from).collect())
+    }
+
+    fn dialog_number(&self, dialog_path: &Path) -> Result<i32, String> {
+        match self.value(DIALOG_NUMBER.0) {
+            Some(number) => number.parse().map_err(|_| format!("invalid dialog number {:?}", number)),
+            None => Ok(dialog_path.file_stem().and_then(|stem| stem.to_str()).and_then(|stem| stem.parse().ok()).unwrap_or(0))
+        }
+    }
+
+    // Falls back on the extension of the text file, then JSON.
+    fn text_format(&self, text_path: &Path) -> Result<TextFormat, String> {
+        match self.value(FORMAT.0) {
+            Some("json") => Ok(TextFormat::Json),
+            Some("ron") => Ok(TextFormat::Ron),
+            Some(other) => Err(format!("unknown text format {:?}", other)),
+            None => Ok(TextFormat::from_path(text_path).unwrap_or(TextFormat::Json))
+        }
+    }
+}
+
+const DRY_RUN: (&str, bool) = ("--dry-run", false);
+const DIALOG_NUMBER: (&str, bool) = ("--dialog-number", true);
+const FORMAT: (&str, bool) = ("--format", true);
+
+fn write_output(path: &Path, contents: &[u8], dry_run: bool) -> Result<(), String> {
+    if dry_run {
+        println!("would write {} ({} bytes)", path.display(), contents.len());
+        return Ok(());
+    }
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|error| format!("{}: {}", parent.display(), error))?;
+    }
+    fs::write(path, contents).map_err(|error| format!("{}: {}", path.display(), error))
+}
+
+fn to_blitz_bytes<T: serde::Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![];
+    serde_blitz3d::to_writer(&mut bytes, value).map_err(|error| error.to_string())?;
+    Ok(bytes)
+}
+
+fn read_dialog(path: &Path) -> Result<Dialog, String> {
+    Dialog::from_dia(path).map_err(|error| error.to_string())
+}
+
+fn lowercase_extension(path: &Path) -> String {
+    path.extension().and_then(|extension| extension.to_str()).unwrap_or_default().to_ascii_lowercase()
+}
+
+fn file_kind(path: &Path) -> Result<FileKind, String> {
+    FileKind::from_path(path).ok_or_else(|| String::from("not a .wlv, .dia or .wop file"))
+}
+
+// Runs a command on every file given, reporting failures without stopping. Returns whether all succeeded.
+fn for_each_file(files: &[PathBuf], command: fn(&Path) -> Result<(), String>) -> bool {
+    let mut succeeded = true;
+    for path in files {
+        if let Err(error) = command(path) {
+            eprintln!("{}: {}", path.display(), error);
+            succeeded = false;
+        }
+    }
+    succeeded
+}
+
+// A dry run prints the report rather than writing it.
+fn convert(input: &Path, output: &Path, options: &BatchOptions, report_path: Option<&Path>) -> bool {
+    let report = convert_batch(input, output, options, |file| match file.status {
+        FileStatus::Converted => println!("{} -> {}: {}", file.input.display(), file.output.display(), file.message),
+        FileStatus::Skipped => println!("skipped {}: {}", file.input.display(), file.message),
+        FileStatus::Failed => eprintln!("{}: {}", file.input.display(), file.message)
+    });
+    let report = match report {
+        Ok(report) => report,
+        Err(error) => {
+            eprintln!("{}: {}", input.display(), error);
+            return false;
+        }
+    };
+    println!("{} converted, {} skipped, {} failed in {:.0} ms", report.converted, report.skipped, report.failed, report.milliseconds);
+    if let Some(path) = report_path {
+        if options.dry_run {
+            println!("{}", report.to_json());
+        } else if let Err(error) = fs::write(path, report.to_json()) {
+            eprintln!("{}: {}", path.display(), error);
+            return false;
+        }
+    }
+    report.is_success()
+}
+
+fn info(path: &Path) -> Result<(), String> {
+    println!("{}:", path.display());
+    match file_kind(path)? {
+        FileKind::Level => {
+            let (wlv, detection) = AnyWlv::from_file(path).map_err(|error| error.to_string())?;
+            println!("  format: {}", detection);
+            match wlv {
+                AnyWlv::Beta(beta) => {
+                    println!("  size: {}x{}", beta.terrain().width(), beta.terrain().height());
+                    println!("  objects: {}", beta.objects().len());
+                },
+                AnyWlv::Latest(level) => {
+                    println!("  size: {}x{}", level.terrain().width(), level.terrain().height());
+                    println!("  objects: {}", level.objects().len());
+                    println!("  level texture: {}", level.level_texture().name());
+                    println!("  water texture: {}", level.water_texture().name());
+                    println!("  music: {}, weather: {}, edge style: {}", level.music().0, level.weather().0, level.edge_style().0);
+                    if !level.adventure_title().is_empty() {
+                        println!("  adventure title: {}", level.adventure_title());
+                    }
+                }
+            }
+        },
+        FileKind::Dialog => {
+            let dialog = read_dialog(path)?;
+            let replies: usize = dialog.interchanges().iter().map(|interchange| interchange.replies().len()).sum();
+            println!("  interchanges: {}", dialog.interchange_count());
+            println!("  replies: {}", replies);
+            println!("  askabouts: {}", dialog.askabouts().len());
+        },
+        FileKind::Object => {
+            let object = GameObject::from_wop(path).map_err(|error| error.to_string())?;
+            println!("  model: {}", object.model_name());
+            println!("  texture: {}", object.texture_name());
+            println!("  logic: type {}, subtype {}", object.logic().object_type(), object.logic().object_subtype());
+        }
+    }
+    Ok(())
+}
+
+fn dump(path: &Path) -> Result<(), String> {
+    match file_kind(path)? {
+        FileKind::Level => println!("{:#?}", AnyWlv::from_file(path).map_err(|error| error.to_string())?.0),
+        FileKind::Dialog => println!("{:#?}", read_dialog(path)?),
+        FileKind::Object => println!("{:#?}", GameObject::from_wop(path).map_err(|error| error.to_string())?)
+    }
+    Ok(())
+}
+
+fn validate(path: &Path) -> Result<(), String> {
+    let problems = match file_kind(path)? {
+        FileKind::Level => {
+            let (_, detection) = AnyWlv::from_file(path).map_err(|error| error.to_string())?;
+            println!("{}: ok, {}", path.display(), detection);
+            return Ok(());
+        },
+        FileKind::Dialog => dialog_problems(&read_dialog(path)?),
+        FileKind::Object => {
+            let bytes = fs::read(path).map_err(|error| error.to_string())?;
+            let mut reader = bytes.as_slice();
+            let _: GameObject = serde_blitz3d::from_reader(&mut reader).map_err(|error| error.to_string())?;
+            if reader.is_empty() { vec![] } else { vec![format!("{} bytes left over", reader.len())] }
+        }
+    };
+    if problems.is_empty() {
+        println!("{}: ok", path.display());
+        return Ok(());
+    }
+    for problem in &problems {
+        println!("{}: {}", path.display(), problem);
+    }
+    Err(format!("{} problem(s)", problems.len()))
+}
+
+fn dialog_problems(dialog: &Dialog) -> Vec<String> {
+    let mut problems = vec![];
+    let count = dialog.interchange_count() as i32;
+    let layout = BodyLayout::default();
+    for (i, interchange) in dialog.interchanges().iter().enumerate() {
+        for overflow in layout.measure(interchange.body()) {
+            problems.push(format!("interchange {}: {}", i, overflow));
+        }
+        for (j, reply) in interchange.replies().iter().enumerate() {
+            if let Some(destination) = reply.fnc().destination_interchange().filter(|&destination| destination < 0 || destination >= count) {
+                problems.push(format!("interchange {} reply {}: goes to missing interchange {}", i, j, destination));
+            }
+        }
+    }
+    for (i, askabout) in dialog.askabouts().iter().enumerate() {
+        if askabout.interchange < 0 || askabout.interchange >= count {
+            problems.push(format!("askabout {}: goes to missing interchange {}", i, askabout.interchange));
+        }
+    }
+    problems
+}
+
+fn export(input: &Path, output: &Path, format: ExportFormat, dialog_number: i32, dry_run: bool) -> bool {
+    let result = read_dialog(input).and_then(|dialog| {
+        let title = input.file_stem().and_then(|stem| stem.to_str()).unwrap_or("Dialog");
+        let text = match format {
+            ExportFormat::Po => dialog.to_po(dialog_number),
+            ExportFormat::Csv => dialog.to_csv(dialog_number),
+            ExportFormat::Markdown => dialog.to_screenplay(title, ScreenplayFormat::Markdown),
+            ExportFormat::Html => dialog.to_screenplay(title, ScreenplayFormat::Html)
+        };
+        write_output(output, text.as_bytes(), dry_run)
+    });
+    report(input, result)
+}
+
+fn import(input: &Path, translations: &Path, output: &Path, dialog_number: i32, dry_run: bool) -> bool {
+    let result = read_dialog(input).and_then(|dialog| {
+        let text = fs::read_to_string(translations).map_err(|error| format!("{}: {}", translations.display(), error))?;
+        let translations = match lowercase_extension(translations).as_str() {
+            "po" => Translations::from_po(&text),
+            "csv" => Translations::from_csv(&text),
+            _ => return Err(format!("{}: translations should be a .po or .csv file", translations.display()))
+        }.map_err(|error| error.to_string())?;
+        let (translated, warnings) = dialog.translated(dialog_number, &translations);
+        for warning in warnings {
+            println!("warning: {}", warning);
+        }
+        write_output(output, &to_blitz_bytes(&translated)?, dry_run)
+    });
+    report(input, result)
+}
+
+fn to_text(input: &Path, output: &Path, format: TextFormat, dry_run: bool) -> bool {
+    let result = TextDocument::from_binary_file(input).map_err(|error| error.to_string()).and_then(|document| {
+        let text = document.to_text(format).map_err(|error| error.to_string())?;
+        write_output(output, text.as_bytes(), dry_run)
+    });
+    report(input, result)
+}
+
+fn to_binary(input: &Path, output: &Path, format: TextFormat, dry_run: bool) -> bool {
+    let result = fs::read_to_string(input).map_err(|error| error.to_string()).and_then(|text| {
+        let document = TextDocument::from_text(&text, format).map_err(|error| error.to_string())?;
+        if FileKind::from_path(output).is_some_and(|kind| kind != document.kind()) {
+            return Err(format!("{} doesn't have the right extension for a {:?} file", output.display(), document.kind()));
+        }
+        write_output(output, &document.to_binary().map_err(|error| error.to_string())?, dry_run)
+    });
+    report(input, result)
+}
+
+fn report(input: &Path, result: Result<(), String>) -> bool {
+    if let Err(error) = &result {
+        eprintln!("{}: {}", input.display(), error);
+    }
+    result.is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn parse(line: &str) -> Result<Invocation, String> {
+        Invocation::parse(&args(line))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("convert --help"), Ok(Invocation::Help));
+        assert_eq!(parse("convert in out --recursive --format openwa --jobs 3 --report r.json --dry-run"), Ok(Invocation::Convert {
+            input: "in".into(),
+            output: "out".into(),
+            options: BatchOptions { recursive: true, format: Some(WlvFormat::OpenWa), dry_run: true, threads: 3 },
+            report: Some("r.json".into()),
+        }));
+        assert_eq!(parse("info a.wlv b.dia"), Ok(Invocation::Info(vec!["a.wlv".into(), "b.dia".into()])));
+        assert_eq!(parse("dump a.wop"), Ok(Invocation::Dump(vec!["a.wop".into()])));
+        assert_eq!(parse("validate a.wlv"), Ok(Invocation::Validate(vec!["a.wlv".into()])));
+        assert_eq!(parse("export dialogs/12.dia out.HTM"), Ok(Invocation::Export {
+            dialog: "dialogs/12.dia".into(), output: "out.HTM".into(), format: ExportFormat::Html, dialog_number: 12, dry_run: false,
+        }));
+        assert_eq!(parse("export a.dia out.txt --format csv --dialog-number 4"), Ok(Invocation::Export {
+            dialog: "a.dia".into(), output: "out.txt".into(), format: ExportFormat::Csv, dialog_number: 4, dry_run: false,
+        }));
+        assert_eq!(parse("import 3.dia fr.po out.dia --dry-run"), Ok(Invocation::Import {
+            dialog: "3.dia".into(), translations: "fr.po".into(), output: "out.dia".into(), dialog_number: 3, dry_run: true,
+        }));
+        assert_eq!(parse("to-text a.wlv a.ron"), Ok(Invocation::ToText { input: "a.wlv".into(), output: "a.ron".into(), format: TextFormat::Ron, dry_run: false }));
+        assert_eq!(parse("to-binary a.txt a.wlv"), Ok(Invocation::ToBinary { input: "a.txt".into(), output: "a.wlv".into(), format: TextFormat::Json, dry_run: false }));
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        for line in [
+            "",
+            "frobnicate a.wlv",
+            "info",
+            "info a.wlv --recursive",
+            "convert in",
+            "convert in out extra",
+            "convert in out --jobs",
+            "convert in out --jobs many",
+            "convert in out --format beta",
+            "export a.dia out.txt",
+            "export a.dia out.po --dialog-number x",
+            "import a.dia fr.po",
+            "to-text a.wlv a.json --format yaml",
+        ] {
+            assert!(parse(line).is_err(), "{:?} should be a usage error", line);
+            assert_eq!(exit_code(&args(line)), EXIT_USAGE, "{:?}", line);
+        }
+        assert_eq!(exit_code(&args("--help")), EXIT_SUCCESS);
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        let root = std::env::temp_dir().join(format!("waust-cli-{}", std::process::id()));
+        fs::create_dir_all(root.join("in")).unwrap();
+        let level = root.join("in").join("level.wlv");
+        Level::new(2, 2).to_file(&level).unwrap();
+        let broken = root.join("broken.dia");
+        Dialog::new(vec![Interchange::plain_text_with_reply(vec![String::from("Hi")], Reply::continue_to(5, String::from("Bye"), Cmd::none()))], vec![], String::new()).to_file(&broken).unwrap();
+        let path = |path: &Path| path.to_str().unwrap().to_owned();
+
+        let validate = exit_code(&[String::from("validate"), path(&level)]);
+        let problems = exit_code(&[String::from("validate"), path(&broken)]);
+        let missing = exit_code(&[String::from("info"), path(&root.join("missing.wlv"))]);
+        let (output, report) = (root.join("out"), root.join("report.json"));
+        let dry_run = exit_code(&[String::from("convert"), path(&root.join("in")), path(&output), String::from("--report"), path(&report), String::from("--dry-run")]);
+        let text = root.join("level.json");
+        let to_text = exit_code(&[String::from("to-text"), path(&level), path(&text), String::from("--dry-run")]);
+        let written = (output.exists(), report.exists(), text.exists());
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!((validate, problems, missing), (EXIT_SUCCESS, EXIT_FAILURE, EXIT_FAILURE));
+        assert_eq!((dry_run, to_text), (EXIT_SUCCESS, EXIT_SUCCESS));
+        assert_eq!(written, (false, false, false));
+    }
+
+    #[test]
+    fn test_translation_extensions_ignore_case() {
+        let root = std::env::temp_dir().join(format!("waust-cli-case-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let (dialog, translations) = (root.join("1.dia"), root.join("T.PO"));
+        Dialog::new(vec![Interchange::plain_text(vec![String::from("Hi")])], vec![], String::new()).to_file(&dialog).unwrap();
+        let path = |path: &Path| path.to_str().unwrap().to_owned();
+
+        let export = exit_code(&[String::from("export"), path(&dialog), path(&translations)]);
+        let import = exit_code(&[String::from("import"), path(&dialog), path(&translations), path(&root.join("out.dia")), String::from("--dry-run")]);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!((export, import), (EXIT_SUCCESS, EXIT_SUCCESS));
+    }
+}
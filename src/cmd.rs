@@ -1,6 +1,8 @@
+use crate::*;
 use serde::*;
+use std::fmt;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub struct Cmd {
     pub id: i32,
     pub data1: i32,
@@ -35,4 +37,40 @@ impl Cmd {
     pub fn toggle(target_id: i32) -> Self {
         Cmd::new(3, target_id, 0, 0, 0)
     }
-}
\ No newline at end of file
+
+    // 22 and 23 are the game's "Set AskAbout Active" and "Set AskAbout Repeat" Cmds,
+    // which take the dialog number, the askabout and the new value in that order.
+    pub fn set_askabout_active(dialog_number: i32, askabout: i32, active: AskAboutActive) -> Self {
+        Cmd::new(22, dialog_number, askabout, active.0, 0)
+    }
+
+    pub fn set_askabout_repeat(dialog_number: i32, askabout: i32, repeat: AskAboutRepeat) -> Self {
+        Cmd::new(23, dialog_number, askabout, repeat.0, 0)
+    }
+
+    /// The ID of the objects this activates, deactivates or toggles.
+    pub fn target_id(&self) -> Option<i32> {
+        matches!(self.id, 1..=3).then_some(self.data1)
+    }
+
+    /// Does nothing unless this Cmd has a target.
+    pub fn set_target_id(&mut self, target_id: i32) {
+        if self.target_id().is_some() {
+            self.data1 = target_id;
+        }
+    }
+}
+
+impl fmt::Display for Cmd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.id {
+            0 => write!(f, "none"),
+            1 => write!(f, "activate {}", self.data1),
+            2 => write!(f, "deactivate {}", self.data1),
+            3 => write!(f, "toggle {}", self.data1),
+            22 => write!(f, "set dialog {} askabout {} to {}", self.data1, self.data2, AskAboutActive(self.data3)),
+            23 => write!(f, "set dialog {} askabout {} repeat to {}", self.data1, self.data2, AskAboutRepeat(self.data3)),
+            id => write!(f, "command {} ({}, {}, {}, {})", id, self.data1, self.data2, self.data3, self.data4)
+        }
+    }
+}
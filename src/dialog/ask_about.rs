@@ -1,15 +1,104 @@
+use crate::*;
 use serde::*;
+use std::fmt;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Whether an askabout is offered. An askabout can also take its state from
+/// an entry in the master askabout table of the player's save, shared across the adventure.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct AskAboutActive(pub i32);
+
+impl AskAboutActive {
+    pub const INACTIVE: Self = Self(0);
+    pub const ACTIVE: Self = Self(1);
+    /// The game reads an active value of 1000 or more as the entry 1000 below it
+    /// in the save's master askabout table, which has 1000 entries.
+    pub const MASTER_OFFSET: i32 = 1000;
+    pub const MASTER_COUNT: usize = 1000;
+
+    /// Takes its state from the given entry of the master askabout table. Returns None if there's no such entry.
+    pub fn master(index: usize) -> Option<Self> {
+        if index < Self::MASTER_COUNT {
+            Some(Self(Self::MASTER_OFFSET + index as i32))
+        } else {
+            None
+        }
+    }
+
+    pub fn master_index(&self) -> Option<usize> {
+        let index = self.0 - Self::MASTER_OFFSET;
+        if (0..Self::MASTER_COUNT as i32).contains(&index) {
+            Some(index as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Works out whether the askabout is offered, given the master askabout table from the player's save.
+    pub fn is_active(&self, master_askabout_active: &[bool]) -> bool {
+        match self.master_index() {
+            Some(index) => master_askabout_active.get(index).copied().unwrap_or(false),
+            None => *self != Self::INACTIVE
+        }
+    }
+
+    pub fn is_active_in(&self, save: &PlayerSave) -> bool {
+        self.is_active(save.master_askabout_active())
+    }
+}
+
+impl fmt::Display for AskAboutActive {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (*self, self.master_index()) {
+            (_, Some(index)) => write!(f, "master askabout {}", index),
+            (Self::INACTIVE, _) => write!(f, "inactive"),
+            (Self::ACTIVE, _) => write!(f, "active"),
+            (Self(other), _) => write!(f, "active ({})", other)
+        }
+    }
+}
+
+/// How many more times an askabout can be picked before it disappears.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct AskAboutRepeat(pub i32);
+
+impl AskAboutRepeat {
+    pub const UNLIMITED: Self = Self(-1);
+    pub const ONCE: Self = Self(1);
+
+    pub fn times(count: i32) -> Self {
+        assert!(count >= 0, "repeat count must not be negative");
+        Self(count)
+    }
+
+    pub fn remaining(&self) -> Option<i32> {
+        if self.0 < 0 {
+            None
+        } else {
+            Some(self.0)
+        }
+    }
+}
+
+impl fmt::Display for AskAboutRepeat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.remaining() {
+            None => write!(f, "unlimited"),
+            Some(1) => write!(f, "once"),
+            Some(count) => write!(f, "{} times", count)
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct AskAbout {
     pub text: String,
-    pub active: i32,
+    pub active: AskAboutActive,
     pub interchange: i32,
-    pub repeat: i32
+    pub repeat: AskAboutRepeat
 }
 
 impl AskAbout {
-    pub fn new(text: String, active: i32, interchange: i32, repeat: i32) -> Self {
+    pub fn new(text: String, active: AskAboutActive, interchange: i32, repeat: AskAboutRepeat) -> Self {
         Self {
             text,
             active,
@@ -21,4 +110,55 @@ impl AskAbout {
     pub fn find_and_replace(&mut self, target: &str, replacement: &str) {
         self.text = self.text.replace(target, replacement);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_master_askabouts() {
+        assert_eq!(AskAboutActive::master(0), Some(AskAboutActive(1000)));
+        assert_eq!(AskAboutActive::master(999), Some(AskAboutActive(1999)));
+        assert_eq!(AskAboutActive::master(AskAboutActive::MASTER_COUNT), None);
+        assert_eq!(AskAboutActive(999).master_index(), None);
+        assert_eq!(AskAboutActive(1000).master_index(), Some(0));
+        assert_eq!(AskAboutActive(1999).master_index(), Some(999));
+        assert_eq!(AskAboutActive(2000).master_index(), None);
+
+        let mut table = [false; AskAboutActive::MASTER_COUNT];
+        table[5] = true;
+        assert!(AskAboutActive(1005).is_active(&table));
+        assert!(!AskAboutActive(1006).is_active(&table));
+        assert!(!AskAboutActive(1005).is_active(&[]));
+        assert!(AskAboutActive::ACTIVE.is_active(&table));
+        assert!(AskAboutActive(2000).is_active(&table));
+        assert!(!AskAboutActive::INACTIVE.is_active(&table));
+
+        // All zeros is a valid save, with empty strings and everything inactive.
+        let mut save = PlayerSave::from_bytes(&[0; 40000]).unwrap();
+        assert!(!AskAboutActive(1999).is_active_in(&save));
+        assert_eq!(save.set_master_askabout_active(999, true), Some(false));
+        assert!(AskAboutActive(1999).is_active_in(&save));
+        assert_eq!(save.set_master_askabout_active(AskAboutActive::MASTER_COUNT, true), None);
+    }
+
+    #[test]
+    fn test_askabout_unlock() {
+        let mut dialog = Dialog::default();
+        dialog.add_empty_interchange();
+        dialog.add_askabout(AskAbout::new(String::from("Stinkers"), AskAboutActive::INACTIVE, 0, AskAboutRepeat::UNLIMITED));
+        assert_eq!(dialog.add_askabout_unlock(0, 4, 0, String::from("Tell me"), ReplyFunction::end(0)), Some(0));
+        assert_eq!(dialog.add_askabout_unlock(1, 4, 0, String::from("Tell me"), ReplyFunction::end(0)), None);
+        assert_eq!(dialog.add_askabout_unlock(0, 4, 1, String::from("Tell me"), ReplyFunction::end(0)), None);
+        assert_eq!(dialog.interchanges()[0].replies().len(), 1);
+    }
+
+    #[test]
+    fn test_repeat_remaining() {
+        assert_eq!(AskAboutRepeat::UNLIMITED.remaining(), None);
+        assert_eq!(AskAboutRepeat(-5).remaining(), None);
+        assert_eq!(AskAboutRepeat::ONCE.remaining(), Some(1));
+        assert_eq!(AskAboutRepeat::times(0).remaining(), Some(0));
+    }
+}
@@ -1,17 +1,17 @@
 use crate::*;
 use serde::*;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 pub struct Body {
-    text_lines: Vec<String>,
-    text_commands: Vec<TextCommand>
+    text_lines: VecLen<String>,
+    text_commands: VecLen<TextCommand>
 }
 
 impl Body {
     pub fn new(text_lines: Vec<String>, text_commands: Vec<TextCommand>) -> Self {
         Self {
-            text_lines,
-            text_commands
+            text_lines: VecLen(text_lines),
+            text_commands: VecLen(text_commands)
         }
     }
 
@@ -24,19 +24,19 @@ impl Body {
     }
 
     pub fn text_lines(&self) -> &[String] {
-        &self.text_lines
+        &self.text_lines.0
     }
 
     pub fn text_lines_mut(&mut self) -> &mut [String] {
-        &mut self.text_lines
+        &mut self.text_lines.0
     }
 
     pub fn text_commands(&self) -> &[TextCommand] {
-        &self.text_commands
+        &self.text_commands.0
     }
 
     pub fn find_and_replace(&mut self, target: &str, replacement: &str) {
-        for text in &mut self.text_lines {
+        for text in &mut self.text_lines.0 {
             *text = text.replace(target, replacement);
         }
     }
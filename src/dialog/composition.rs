@@ -0,0 +1,144 @@
+use crate::*;
+use std::ops::Range;
+
+/// Where replies in a reusable fragment should go when they leave the fragment.
+/// Once the fragment is in place, wire_exits points them at an interchange of the host dialog.
+pub const FRAGMENT_EXIT: i32 = -1;
+
+impl Dialog {
+    /// Adds the fragment's interchanges and askabouts to the end of this dialog.
+    /// Returns the offset that the fragment's interchange indices were moved by.
+    pub fn append_dialog(&mut self, fragment: &Dialog) -> i32 {
+        self.embed_at_index(self.interchange_count(), fragment)
+    }
+
+    /// Finds the placeholder interchange with the given label, i.e. one with only the label for a body and no replies.
+    pub fn find_placeholder(&self, label: &str) -> Option<usize> {
+        self.interchanges.iter().position(|interchange| interchange.replies().is_empty() && interchange.body_text_lines() == [label])
+    }
+
+    /// Replaces the placeholder interchange with the given label with the fragment's interchanges and adds its askabouts to the end.
+    /// Everything that pointed at the placeholder goes to the start of the fragment instead.
+    /// Returns the offset that the fragment's interchange indices were moved by, or None if there's no such placeholder.
+    pub fn embed_at(&mut self, label: &str, fragment: &Dialog) -> Option<i32> {
+        let index = self.find_placeholder(label)?;
+        let offset = self.embed_at_index(index, fragment);
+        let placeholder = offset + fragment.interchange_count() as i32;
+        self.relocate(|destination| if destination == placeholder {
+            offset
+        } else if destination > placeholder {
+            destination - 1
+        } else {
+            destination
+        });
+        self.interchanges.remove(placeholder as usize);
+        Some(offset)
+    }
+
+    /// Inserts the fragment's interchanges at the given index and adds its askabouts to the end.
+    /// Interchanges from the index onwards move up to make room, and everything pointing at them is relocated.
+    /// Returns the offset that the fragment's interchange indices were moved by.
+    pub fn embed_at_index(&mut self, index: usize, fragment: &Dialog) -> i32 {
+        assert!(index <= self.interchange_count(), "cannot embed past the end of the dialog");
+        let offset = index as i32;
+        let length = fragment.interchange_count() as i32;
+
+        self.relocate(|destination| if destination >= offset { destination + length } else { destination });
+        let mut fragment = fragment.clone();
+        fragment.relocate(|destination| if destination == FRAGMENT_EXIT { destination } else { destination + offset });
+
+        self.interchanges.splice(index..index, fragment.interchanges);
+        self.askabouts.extend(fragment.askabouts);
+        offset
+    }
+
+    /// Points every fragment exit among the given interchanges at the destination.
+    /// Returns how many replies were wired up.
+    pub fn wire_exits(&mut self, interchanges: Range<usize>, destination: i32) -> usize {
+        let mut count = 0;
+        for interchange in &mut self.interchanges[interchanges] {
+            for reply in interchange.replies_mut() {
+                if reply.fnc().destination_interchange() == Some(FRAGMENT_EXIT) {
+                    reply.fnc_mut().set_destination_interchange(destination);
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    fn relocate<F>(&mut self, mut relocation: F)
+    where
+        F: FnMut(i32) -> i32
+    {
+        for interchange in &mut self.interchanges {
+            for reply in interchange.replies_mut() {
+                if let Some(destination) = reply.fnc().destination_interchange() {
+                    reply.fnc_mut().set_destination_interchange(relocation(destination));
+                }
+            }
+        }
+        for askabout in &mut self.askabouts {
+            askabout.interchange = relocation(askabout.interchange);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embed_relocates_and_wires_exits() {
+        let mut host = Dialog::default();
+        host.add_interchange(Interchange::plain_text_with_reply(vec![String::from("Host")], Reply::continue_to(1, String::from("Next"), Cmd::none())));
+        host.add_interchange(Interchange::placeholder(String::from("End")));
+
+        let mut fragment = Dialog::default();
+        fragment.add_interchange(Interchange::plain_text_with_replies(vec![String::from("Riddle")], vec![
+            Reply::continue_to(1, String::from("Guess"), Cmd::none()),
+            Reply::continue_to(FRAGMENT_EXIT, String::from("Leave"), Cmd::none()),
+        ]));
+        fragment.add_interchange(Interchange::plain_text_with_reply(vec![String::from("Right!")], Reply::end(FRAGMENT_EXIT, String::from("OK"), Cmd::none())));
+        fragment.add_askabout(AskAbout::new(String::from("Riddles"), AskAboutActive::ACTIVE, 0, AskAboutRepeat::UNLIMITED));
+
+        let offset = host.embed_at_index(1, &fragment);
+        assert_eq!(offset, 1);
+        assert_eq!(host.wire_exits(1..3, 3), 2);
+
+        assert_eq!(host.interchanges[0].replies()[0].fnc(), ReplyFunction::continue_to(3));
+        assert_eq!(host.interchanges[1].replies()[0].fnc(), ReplyFunction::continue_to(2));
+        assert_eq!(host.interchanges[1].replies()[1].fnc(), ReplyFunction::continue_to(3));
+        assert_eq!(host.interchanges[2].replies()[0].fnc(), ReplyFunction::end(3));
+        assert_eq!(host.askabouts[0].interchange, 1);
+    }
+
+    #[test]
+    fn test_embed_replaces_placeholder() {
+        let mut host = Dialog::default();
+        host.add_interchange(Interchange::plain_text_with_replies(vec![String::from("Host")], vec![
+            Reply::continue_to(1, String::from("Riddle me"), Cmd::none()),
+            Reply::continue_to(2, String::from("Skip"), Cmd::none()),
+        ]));
+        host.add_interchange(Interchange::placeholder(String::from("RIDDLE")));
+        host.add_interchange(Interchange::plain_text_with_reply(vec![String::from("Bye")], Reply::end(0, String::from("OK"), Cmd::none())));
+        host.add_askabout(AskAbout::new(String::from("Riddles"), AskAboutActive::ACTIVE, 1, AskAboutRepeat::UNLIMITED));
+
+        let mut fragment = Dialog::default();
+        fragment.add_interchange(Interchange::plain_text_with_reply(vec![String::from("Riddle")], Reply::continue_to(1, String::from("Guess"), Cmd::none())));
+        fragment.add_interchange(Interchange::plain_text_with_reply(vec![String::from("Right!")], Reply::continue_to(FRAGMENT_EXIT, String::from("OK"), Cmd::none())));
+
+        assert_eq!(host.embed_at("MISSING", &fragment), None);
+        assert_eq!(host.embed_at("RIDDLE", &fragment), Some(1));
+        assert_eq!(host.find_placeholder("RIDDLE"), None);
+        assert_eq!(host.interchange_count(), 4);
+        assert_eq!(host.wire_exits(1..3, 3), 1);
+
+        assert_eq!(host.interchanges[0].replies()[0].fnc(), ReplyFunction::continue_to(1));
+        assert_eq!(host.interchanges[0].replies()[1].fnc(), ReplyFunction::continue_to(3));
+        assert_eq!(host.interchanges[1].replies()[0].fnc(), ReplyFunction::continue_to(2));
+        assert_eq!(host.interchanges[2].replies()[0].fnc(), ReplyFunction::continue_to(3));
+        assert_eq!(host.interchanges[3].body_text_lines(), ["Bye"]);
+        assert_eq!(host.askabouts[0].interchange, 1);
+    }
+}
@@ -0,0 +1,147 @@
+use crate::*;
+use std::fmt;
+
+/// A single difference between two versions of a dialog.
+/// Interchanges, replies and askabouts are compared by index, since that's how everything refers to them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DialogChange {
+    InterchangeAdded { interchange: usize, new: Interchange },
+    InterchangeRemoved { interchange: usize, old: Interchange },
+    BodyChanged { interchange: usize, old: Body, new: Body },
+    ReplyAdded { interchange: usize, reply: usize, new: Reply },
+    ReplyRemoved { interchange: usize, reply: usize, old: Reply },
+    ReplyChanged { interchange: usize, reply: usize, old: Reply, new: Reply },
+    AskAboutAdded { askabout: usize, new: AskAbout },
+    AskAboutRemoved { askabout: usize, old: AskAbout },
+    AskAboutChanged { askabout: usize, old: AskAbout, new: AskAbout },
+    AskAboutTopTextChanged { old: String, new: String },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DialogDiff {
+    changes: Vec<DialogChange>,
+}
+
+impl DialogDiff {
+    pub fn changes(&self) -> &[DialogChange] {
+        &self.changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl Dialog {
+    pub fn diff(&self, other: &Dialog) -> DialogDiff {
+        let mut changes = vec![];
+        for i in 0..self.interchanges.len().max(other.interchanges.len()) {
+            match (self.interchanges.get(i), other.interchanges.get(i)) {
+                (Some(old), Some(new)) => diff_interchange(i, old, new, &mut changes),
+                (Some(old), None) => changes.push(DialogChange::InterchangeRemoved { interchange: i, old: old.clone() }),
+                (None, Some(new)) => changes.push(DialogChange::InterchangeAdded { interchange: i, new: new.clone() }),
+                (None, None) => unreachable!()
+            }
+        }
+        if self.askabout_top_text != other.askabout_top_text {
+            changes.push(DialogChange::AskAboutTopTextChanged { old: self.askabout_top_text.clone(), new: other.askabout_top_text.clone() });
+        }
+        for i in 0..self.askabouts.len().max(other.askabouts.len()) {
+            match (self.askabouts.get(i), other.askabouts.get(i)) {
+                (Some(old), Some(new)) if old == new => {},
+                (Some(old), Some(new)) => changes.push(DialogChange::AskAboutChanged { askabout: i, old: old.clone(), new: new.clone() }),
+                (Some(old), None) => changes.push(DialogChange::AskAboutRemoved { askabout: i, old: old.clone() }),
+                (None, Some(new)) => changes.push(DialogChange::AskAboutAdded { askabout: i, new: new.clone() }),
+                (None, None) => unreachable!()
+            }
+        }
+        DialogDiff { changes }
+    }
+}
+
+fn diff_interchange(interchange: usize, old: &Interchange, new: &Interchange, changes: &mut Vec<DialogChange>) {
+    if old.body() != new.body() {
+        changes.push(DialogChange::BodyChanged { interchange, old: old.body().clone(), new: new.body().clone() });
+    }
+    for reply in 0..old.replies().len().max(new.replies().len()) {
+        match (old.replies().get(reply), new.replies().get(reply)) {
+            (Some(old), Some(new)) if old == new => {},
+            (Some(old), Some(new)) => changes.push(DialogChange::ReplyChanged { interchange, reply, old: old.clone(), new: new.clone() }),
+            (Some(old), None) => changes.push(DialogChange::ReplyRemoved { interchange, reply, old: old.clone() }),
+            (None, Some(new)) => changes.push(DialogChange::ReplyAdded { interchange, reply, new: new.clone() }),
+            (None, None) => unreachable!()
+        }
+    }
+}
+
+fn write_body(f: &mut fmt::Formatter, sign: char, body: &Body) -> fmt::Result {
+    for line in body.to_markup().split('\n') {
+        writeln!(f, "  {} {}", sign, line)?;
+    }
+    Ok(())
+}
+
+fn write_askabout(f: &mut fmt::Formatter, sign: char, askabout: &AskAbout) -> fmt::Result {
+    writeln!(f, "  {} \"{}\" (interchange {}, active {}, repeat {})", sign, askabout.text, askabout.interchange, askabout.active, askabout.repeat)
+}
+
+impl fmt::Display for DialogChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DialogChange::InterchangeAdded { interchange, new } => {
+                writeln!(f, "+ interchange {}", interchange)?;
+                write_body(f, '+', new.body())?;
+                for reply in new.replies() {
+                    writeln!(f, "  + reply {}", reply)?;
+                }
+                Ok(())
+            },
+            DialogChange::InterchangeRemoved { interchange, old } => {
+                writeln!(f, "- interchange {}", interchange)?;
+                write_body(f, '-', old.body())
+            },
+            DialogChange::BodyChanged { interchange, old, new } => {
+                writeln!(f, "~ interchange {} body", interchange)?;
+                write_body(f, '-', old)?;
+                write_body(f, '+', new)
+            },
+            DialogChange::ReplyAdded { interchange, reply, new } => writeln!(f, "+ interchange {} reply {}: {}", interchange, reply, new),
+            DialogChange::ReplyRemoved { interchange, reply, old } => writeln!(f, "- interchange {} reply {}: {}", interchange, reply, old),
+            DialogChange::ReplyChanged { interchange, reply, old, new } => {
+                writeln!(f, "~ interchange {} reply {}", interchange, reply)?;
+                writeln!(f, "  - {}", old)?;
+                writeln!(f, "  + {}", new)
+            },
+            DialogChange::AskAboutAdded { askabout, new } => {
+                writeln!(f, "+ askabout {}", askabout)?;
+                write_askabout(f, '+', new)
+            },
+            DialogChange::AskAboutRemoved { askabout, old } => {
+                writeln!(f, "- askabout {}", askabout)?;
+                write_askabout(f, '-', old)
+            },
+            DialogChange::AskAboutChanged { askabout, old, new } => {
+                writeln!(f, "~ askabout {}", askabout)?;
+                write_askabout(f, '-', old)?;
+                write_askabout(f, '+', new)
+            },
+            DialogChange::AskAboutTopTextChanged { old, new } => {
+                writeln!(f, "~ askabout top text")?;
+                writeln!(f, "  - {}", old)?;
+                writeln!(f, "  + {}", new)
+            }
+        }
+    }
+}
+
+impl fmt::Display for DialogDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.changes.is_empty() {
+            return writeln!(f, "no changes");
+        }
+        for change in &self.changes {
+            write!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
@@ -1,17 +1,17 @@
 use crate::*;
 use serde::*;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
 pub struct Interchange {
     body: Body,
-    replies: Vec<Reply>
+    replies: VecLen<Reply>
 }
 
 impl Interchange {
     pub fn new(body: Body, replies: Vec<Reply>) -> Self {
         Self {
             body,
-            replies
+            replies: VecLen(replies)
         }
     }
 
@@ -31,6 +31,14 @@ impl Interchange {
         Self::new(Body::plain_text(body), replies)
     }
 
+    pub fn body(&self) -> &Body {
+        &self.body
+    }
+
+    pub fn set_body(&mut self, body: Body) {
+        self.body = body;
+    }
+
     pub fn body_text_lines(&self) -> &[String] {
         self.body.text_lines()
     }
@@ -44,16 +52,20 @@ impl Interchange {
     }
 
     pub fn replies(&self) -> &[Reply] {
-        &self.replies
+        &self.replies.0
+    }
+
+    pub fn replies_mut(&mut self) -> &mut [Reply] {
+        &mut self.replies.0
     }
 
     pub fn add_reply(&mut self, reply: Reply) {
-        self.replies.push(reply);
+        self.replies.0.push(reply);
     }
 
     pub fn find_and_replace(&mut self, target: &str, replacement: &str) {
         self.body.find_and_replace(target, replacement);
-        for reply in &mut self.replies {
+        for reply in &mut self.replies.0 {
             reply.find_and_replace(target, replacement);
         }
     }
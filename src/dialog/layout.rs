@@ -0,0 +1,191 @@
+use crate::*;
+use std::fmt;
+
+/// The number of characters that fit on one line of the game's dialog box.
+pub const BODY_LINE_LENGTH: usize = 38;
+/// The number of lines that fit in the game's dialog box.
+pub const BODY_LINE_COUNT: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutOverflow {
+    LineTooLong { line: usize, length: usize },
+    TooManyLines { count: usize },
+}
+
+impl fmt::Display for LayoutOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LayoutOverflow::LineTooLong { line, length } => write!(f, "line {} is {} characters long", line, length),
+            LayoutOverflow::TooManyLines { count } => write!(f, "body has {} lines", count)
+        }
+    }
+}
+
+/// Limits that body text is measured, wrapped and paginated against.
+/// Narrower limits can be used to leave a safety margin,
+/// but text command positions always use the game's line length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLayout {
+    line_length: usize,
+    line_count: usize,
+}
+
+impl Default for BodyLayout {
+    fn default() -> Self {
+        Self::new(BODY_LINE_LENGTH, BODY_LINE_COUNT)
+    }
+}
+
+impl BodyLayout {
+    pub fn new(line_length: usize, line_count: usize) -> Self {
+        assert!(line_length != 0 && line_length <= BODY_LINE_LENGTH, "line length must be between 1 and {}", BODY_LINE_LENGTH);
+        assert!(line_count != 0, "line count must not be 0");
+        Self {
+            line_length,
+            line_count,
+        }
+    }
+
+    pub fn line_length(&self) -> usize {
+        self.line_length
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_count
+    }
+
+    pub fn measure(&self, body: &Body) -> Vec<LayoutOverflow> {
+        let mut overflows = vec![];
+        for (line, text) in body.text_lines().iter().enumerate() {
+            let length = text.chars().count();
+            if length > self.line_length {
+                overflows.push(LayoutOverflow::LineTooLong { line, length });
+            }
+        }
+        let count = body.text_lines().len();
+        if count > self.line_count {
+            overflows.push(LayoutOverflow::TooManyLines { count });
+        }
+        overflows
+    }
+
+    pub fn fits(&self, body: &Body) -> bool {
+        self.measure(body).is_empty()
+    }
+
+    /// Word-wraps every line of the body, moving text commands along with the characters they precede.
+    pub fn wrap(&self, body: &Body) -> Body {
+        let mut text_lines = vec![];
+        // For each original line, where each of its columns ended up.
+        let mut column_maps = vec![];
+        for text in body.text_lines() {
+            let (wrapped, columns) = self.wrap_line(text);
+            let first_line = text_lines.len();
+            column_maps.push(columns.into_iter().map(|(line, column)| (first_line + line, column)).collect::<Vec<_>>());
+            text_lines.extend(wrapped);
+        }
+
+        let text_commands = body.text_commands().iter().map(|command| {
+            let (line, column) = match column_maps.get(command.line()) {
+                Some(columns) => columns[command.column().min(columns.len() - 1)],
+                None => (text_lines.len().saturating_sub(1), text_lines.last().map_or(0, |text: &String| text.chars().count()))
+            };
+            TextCommand::at(command.name().to_owned(), line, column)
+        }).collect();
+
+        Body::new(text_lines, text_commands)
+    }
+
+    /// Wraps the body and splits it into pages that each fit in the dialog box.
+    pub fn paginate(&self, body: &Body) -> Vec<Body> {
+        let wrapped = self.wrap(body);
+        if wrapped.text_lines().is_empty() {
+            return vec![wrapped];
+        }
+        wrapped.text_lines().chunks(self.line_count).enumerate().map(|(page, text_lines)| {
+            let first_line = page * self.line_count;
+            let text_commands = wrapped.text_commands().iter()
+                .filter(|command| command.line().min(wrapped.text_lines().len() - 1) / self.line_count == page)
+                .map(|command| TextCommand::at(command.name().to_owned(), command.line() - first_line, command.column()))
+                .collect();
+            Body::new(text_lines.to_vec(), text_commands)
+        }).collect()
+    }
+
+    // Returns the wrapped lines along with the (line, column) that each original column maps to,
+    // including the column just past the end of the line.
+    fn wrap_line(&self, text: &str) -> (Vec<String>, Vec<(usize, usize)>) {
+        let chars: Vec<char> = text.chars().collect();
+        let mut lines = vec![];
+        let mut columns = Vec::with_capacity(chars.len() + 1);
+        let mut start = 0;
+        loop {
+            let line = lines.len();
+            if chars.len() - start <= self.line_length {
+                columns.extend((start..=chars.len()).map(|i| (line, i - start)));
+                lines.push(chars[start..].iter().collect());
+                break;
+            }
+
+            let limit = start + self.line_length;
+            let (end, next) = match (start + 1..=limit).rev().find(|&i| chars[i] == ' ') {
+                Some(space) => {
+                    let mut end = space;
+                    while end > start + 1 && chars[end - 1] == ' ' {
+                        end -= 1;
+                    }
+                    let mut next = space;
+                    while next < chars.len() && chars[next] == ' ' {
+                        next += 1;
+                    }
+                    (end, next)
+                },
+                None => (limit, limit)
+            };
+
+            columns.extend((start..end).map(|i| (line, i - start)));
+            lines.push(chars[start..end].iter().collect());
+            if next == chars.len() {
+                // Only spaces were left over, so there is no next line for them to move to.
+                columns.extend((end..=next).map(|_| (line, end - start)));
+                break;
+            }
+            // Commands on the dropped spaces apply from the start of the next line.
+            columns.extend((end..next).map(|_| (line + 1, 0)));
+            start = next;
+        }
+        (lines, columns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_moves_commands() {
+        let layout = BodyLayout::new(10, 2);
+        let body = Body::new(
+            vec![String::from("Hello there my friend")],
+            vec![TextCommand::at(String::from("CRED"), 0, 15)]
+        );
+        let wrapped = layout.wrap(&body);
+        assert_eq!(wrapped.text_lines(), ["Hello", "there my", "friend"]);
+        assert_eq!(wrapped.text_commands()[0].line(), 2);
+        assert_eq!(wrapped.text_commands()[0].column(), 0);
+        assert_eq!(layout.measure(&wrapped), [LayoutOverflow::TooManyLines { count: 3 }]);
+
+        let pages = layout.paginate(&body);
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[1].text_lines(), ["friend"]);
+        assert_eq!(pages[1].text_commands()[0].position(), 0);
+    }
+
+    #[test]
+    fn test_wrap_breaks_long_words() {
+        let layout = BodyLayout::new(4, 8);
+        let wrapped = layout.wrap(&Body::one_liner(String::from("abcdefghij")));
+        assert_eq!(wrapped.text_lines(), ["abcd", "efgh", "ij"]);
+        assert!(layout.fits(&wrapped));
+    }
+}
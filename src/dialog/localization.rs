@@ -0,0 +1,427 @@
+use crate::*;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A string that translators need to see, keyed by where it lives in the dialog, e.g. "dia3/ic12/reply2".
+/// Body lines are given as markup so that text commands move with the words they apply to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranslatableString {
+    pub key: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalizationWarning {
+    MissingKey(String),
+    ExtraKey(String),
+    InvalidMarkup { key: String, error: MarkupError },
+    Overflow { key: String, overflow: LayoutOverflow },
+}
+
+impl fmt::Display for LocalizationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LocalizationWarning::MissingKey(key) => write!(f, "{}: no translation, keeping the original", key),
+            LocalizationWarning::ExtraKey(key) => write!(f, "{}: translation doesn't match anything in the dialog", key),
+            LocalizationWarning::InvalidMarkup { key, error } => write!(f, "{}: {}, keeping the original", key, error),
+            LocalizationWarning::Overflow { key, overflow } => write!(f, "{}: {}", key, overflow)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalizationError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for LocalizationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for LocalizationError { }
+
+/// Translated text by key. One set of translations can cover any number of dialogs.
+#[derive(Debug, Clone, Default)]
+pub struct Translations(BTreeMap<String, String>);
+
+impl Translations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key: String, text: String) {
+        self.0.insert(key, text);
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(|text| text.as_str())
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(|key| key.as_str())
+    }
+
+    /// Reads the msgctxt and msgstr of every entry. Entries with an empty msgstr are untranslated and skipped.
+    pub fn from_po(po: &str) -> Result<Self, LocalizationError> {
+        #[derive(PartialEq)]
+        enum Field { None, Context, Id, Str }
+
+        let mut translations = Self::new();
+        let mut field = Field::None;
+        let mut context: Option<String> = None;
+        let mut text = String::new();
+        let mut flush = |context: &mut Option<String>, text: &mut String| {
+            if let Some(key) = context.take() {
+                if !text.is_empty() {
+                    translations.insert(key, std::mem::take(text));
+                }
+            }
+            text.clear();
+        };
+
+        for (index, line) in po.lines().enumerate() {
+            let line = line.trim();
+            let error = |message: &str| LocalizationError { line: index + 1, message: message.to_owned() };
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (keyword, quoted) = match line.split_once(' ') {
+                Some((keyword, quoted)) if !line.starts_with('"') => (keyword, quoted.trim()),
+                _ => ("", line)
+            };
+            let value = unquote_po(quoted).ok_or_else(|| error("expected a quoted string"))?;
+            match keyword {
+                "msgctxt" => {
+                    flush(&mut context, &mut text);
+                    context = Some(value);
+                    field = Field::Context;
+                },
+                "msgid" => {
+                    if field != Field::Context {
+                        flush(&mut context, &mut text);
+                    }
+                    field = Field::Id;
+                },
+                "msgstr" => {
+                    text = value;
+                    field = Field::Str;
+                },
+                "" => match field {
+                    Field::Context => context.get_or_insert_with(String::new).push_str(&value),
+                    Field::Str => text.push_str(&value),
+                    Field::Id => {},
+                    Field::None => return Err(error("string continues nothing"))
+                },
+                _ => return Err(error("unknown keyword"))
+            }
+        }
+        flush(&mut context, &mut text);
+        Ok(translations)
+    }
+
+    /// Reads the "key" and "translation" columns. Rows with an empty translation are untranslated and skipped.
+    pub fn from_csv(csv: &str) -> Result<Self, LocalizationError> {
+        let rows = parse_csv(csv)?;
+        let mut rows = rows.into_iter();
+        let header = rows.next().unwrap_or_default();
+        let column = |name: &str| header.iter().position(|column| column == name)
+            .ok_or_else(|| LocalizationError { line: 1, message: format!("missing \"{}\" column", name) });
+        let key_column = column("key")?;
+        let translation_column = column("translation")?;
+
+        let mut translations = Self::new();
+        for row in rows {
+            if let (Some(key), Some(text)) = (row.get(key_column), row.get(translation_column)) {
+                if !key.is_empty() && !text.is_empty() {
+                    translations.insert(key.clone(), text.clone());
+                }
+            }
+        }
+        Ok(translations)
+    }
+}
+
+impl Dialog {
+    pub fn translatable_strings(&self, dialog_number: i32) -> Vec<TranslatableString> {
+        let mut strings = vec![];
+        let mut push = |key: String, text: String| strings.push(TranslatableString { key, text });
+        for (i, interchange) in self.interchanges.iter().enumerate() {
+            let body = interchange.body();
+            let mut renderer = MarkupRenderer::default();
+            for (j, text) in body.text_lines().iter().enumerate() {
+                let commands = body.text_commands().iter().filter(|command| command.line() == j);
+                push(line_key(dialog_number, i, j), renderer.render_line(text, commands));
+            }
+            for (j, reply) in interchange.replies().iter().enumerate() {
+                push(reply_key(dialog_number, i, j), reply.text().to_owned());
+            }
+        }
+        if !self.askabouts.is_empty() || !self.askabout_top_text.is_empty() {
+            push(askabout_top_text_key(dialog_number), self.askabout_top_text.clone());
+        }
+        for (i, askabout) in self.askabouts.iter().enumerate() {
+            push(askabout_key(dialog_number, i), askabout.text.clone());
+        }
+        strings
+    }
+
+    pub fn to_po(&self, dialog_number: i32) -> String {
+        let mut po = String::from("msgid \"\"\nmsgstr \"Content-Type: text/plain; charset=UTF-8\\n\"\n");
+        for string in self.translatable_strings(dialog_number) {
+            po.push_str(&format!("\nmsgctxt {}\nmsgid {}\nmsgstr \"\"\n", quote_po(&string.key), quote_po(&string.text)));
+        }
+        po
+    }
+
+    pub fn to_csv(&self, dialog_number: i32) -> String {
+        let mut csv = String::from("key,source,translation\n");
+        for string in self.translatable_strings(dialog_number) {
+            csv.push_str(&format!("{},{},\n", quote_csv(&string.key), quote_csv(&string.text)));
+        }
+        csv
+    }
+
+    /// Returns a copy of the dialog with its text replaced by the translations.
+    /// Structure, reply functions and commands are left alone, and anything untranslated keeps its original text.
+    pub fn translated(&self, dialog_number: i32, translations: &Translations) -> (Dialog, Vec<LocalizationWarning>) {
+        let mut dialog = self.clone();
+        let mut warnings = vec![];
+        let mut used_keys = vec![];
+        let mut lookup = |key: String, warnings: &mut Vec<LocalizationWarning>| {
+            let text = translations.get(&key).map(|text| text.to_owned());
+            if text.is_none() {
+                warnings.push(LocalizationWarning::MissingKey(key.clone()));
+            }
+            used_keys.push(key);
+            text
+        };
+
+        let layout = BodyLayout::default();
+        for (i, interchange) in dialog.interchanges.iter_mut().enumerate() {
+            let body = interchange.body();
+            let mut text_lines = Vec::with_capacity(body.text_lines().len());
+            let mut text_commands = vec![];
+            for (j, text) in body.text_lines().iter().enumerate() {
+                let key = line_key(dialog_number, i, j);
+                let parsed = lookup(key.clone(), &mut warnings).and_then(|markup| match parse_markup_line(&markup, j) {
+                    Ok(parsed) => Some(parsed),
+                    Err(error) => {
+                        warnings.push(LocalizationWarning::InvalidMarkup { key, error });
+                        None
+                    }
+                });
+                let (text, commands) = parsed.unwrap_or_else(|| {
+                    (text.clone(), body.text_commands().iter().filter(|command| command.line() == j).cloned().collect())
+                });
+                text_lines.push(text);
+                text_commands.extend(commands);
+            }
+            // Commands that sit past the last line aren't attached to any text.
+            text_commands.extend(body.text_commands().iter().filter(|command| command.line() >= body.text_lines().len()).cloned());
+            let body = Body::new(text_lines, text_commands);
+
+            for overflow in layout.measure(&body) {
+                let key = match overflow {
+                    LayoutOverflow::LineTooLong { line, .. } => line_key(dialog_number, i, line),
+                    LayoutOverflow::TooManyLines { .. } => interchange_key(dialog_number, i)
+                };
+                warnings.push(LocalizationWarning::Overflow { key, overflow });
+            }
+            interchange.set_body(body);
+
+            for (j, reply) in interchange.replies_mut().iter_mut().enumerate() {
+                let key = reply_key(dialog_number, i, j);
+                if let Some(text) = lookup(key.clone(), &mut warnings) {
+                    reply.set_text(text);
+                }
+                check_line_length(key, reply.text(), &layout, &mut warnings);
+            }
+        }
+
+        if !dialog.askabouts.is_empty() || !dialog.askabout_top_text.is_empty() {
+            let key = askabout_top_text_key(dialog_number);
+            if let Some(text) = lookup(key.clone(), &mut warnings) {
+                dialog.askabout_top_text = text;
+            }
+            check_line_length(key, &dialog.askabout_top_text, &layout, &mut warnings);
+        }
+        for (i, askabout) in dialog.askabouts.iter_mut().enumerate() {
+            let key = askabout_key(dialog_number, i);
+            if let Some(text) = lookup(key.clone(), &mut warnings) {
+                askabout.text = text;
+            }
+            check_line_length(key, &askabout.text, &layout, &mut warnings);
+        }
+
+        let prefix = format!("dia{}/", dialog_number);
+        for key in translations.keys().filter(|key| key.starts_with(&prefix)) {
+            if !used_keys.iter().any(|used_key| used_key == key) {
+                warnings.push(LocalizationWarning::ExtraKey(key.to_owned()));
+            }
+        }
+        (dialog, warnings)
+    }
+}
+
+fn interchange_key(dialog_number: i32, interchange: usize) -> String {
+    format!("dia{}/ic{}", dialog_number, interchange)
+}
+
+fn line_key(dialog_number: i32, interchange: usize, line: usize) -> String {
+    format!("dia{}/ic{}/line{}", dialog_number, interchange, line)
+}
+
+fn reply_key(dialog_number: i32, interchange: usize, reply: usize) -> String {
+    format!("dia{}/ic{}/reply{}", dialog_number, interchange, reply)
+}
+
+fn askabout_key(dialog_number: i32, askabout: usize) -> String {
+    format!("dia{}/askabout{}", dialog_number, askabout)
+}
+
+fn askabout_top_text_key(dialog_number: i32) -> String {
+    format!("dia{}/askabout_top_text", dialog_number)
+}
+
+fn check_line_length(key: String, text: &str, layout: &BodyLayout, warnings: &mut Vec<LocalizationWarning>) {
+    let length = text.chars().count();
+    if length > layout.line_length() {
+        warnings.push(LocalizationWarning::Overflow { key, overflow: LayoutOverflow::LineTooLong { line: 0, length } });
+    }
+}
+
+fn quote_po(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(c)
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn unquote_po(quoted: &str) -> Option<String> {
+    let inner = quoted.strip_prefix('"')?.strip_suffix('"')?;
+    let mut text = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next()? {
+                'n' => text.push('\n'),
+                't' => text.push('\t'),
+                other => text.push(other)
+            }
+        } else {
+            text.push(c);
+        }
+    }
+    Some(text)
+}
+
+fn quote_csv(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+fn parse_csv(csv: &str) -> Result<Vec<Vec<String>>, LocalizationError> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                },
+                '"' => quoted = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {},
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+                line += 1;
+            },
+            _ => field.push(c)
+        }
+    }
+    if quoted {
+        return Err(LocalizationError { line, message: String::from("quoted field is never closed") });
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_dialog() -> Dialog {
+        let mut dialog = Dialog::default();
+        dialog.add_interchange(Interchange::new(
+            Body::from_markup("Hello <red>friend</red>, \"welcome\"!").unwrap(),
+            vec![Reply::none(String::from("Thanks, bye"))]
+        ));
+        dialog
+    }
+
+    #[test]
+    fn test_po_round_trip() {
+        let dialog = sample_dialog();
+        assert!(dialog.to_po(3).contains("msgctxt \"dia3/ic0/line0\"\nmsgid \"Hello <red>friend</red>, \\\"welcome\\\"!\"\n"));
+
+        let po = "msgctxt \"dia3/ic0/line0\"\nmsgid \"\"\nmsgstr \"Hallo <red>Freund</red>, \"\n\"\\\"willkommen\\\"!\"\n\n\
+                  msgctxt \"dia3/ic0/reply0\"\nmsgid \"\"\nmsgstr \"Danke\"\n\n\
+                  msgctxt \"dia3/ic9/line0\"\nmsgid \"\"\nmsgstr \"?\"\n";
+        let (translated, warnings) = dialog.translated(3, &Translations::from_po(po).unwrap());
+
+        assert_eq!(translated.interchanges[0].body_text_lines(), ["Hallo Freund, \"willkommen\"!"]);
+        assert_eq!(translated.interchanges[0].body_text_commands()[1].column(), 12);
+        assert_eq!(translated.interchanges[0].replies()[0].text(), "Danke");
+        assert_eq!(warnings, [LocalizationWarning::ExtraKey(String::from("dia3/ic9/line0"))]);
+    }
+
+    #[test]
+    fn test_csv_missing_and_overflow() {
+        let dialog = sample_dialog();
+        let csv = dialog.to_csv(3).replace("\"Thanks, bye\",", "\"Thanks, bye\",\"Danke, tschüss, bis zum nächsten Mal, lieber Freund\"");
+        let (translated, warnings) = dialog.translated(3, &Translations::from_csv(&csv).unwrap());
+
+        assert_eq!(translated.interchanges[0].body_text_lines(), ["Hello friend, \"welcome\"!"]);
+        assert_eq!(warnings, [
+            LocalizationWarning::MissingKey(String::from("dia3/ic0/line0")),
+            LocalizationWarning::Overflow {
+                key: String::from("dia3/ic0/reply0"),
+                overflow: LayoutOverflow::LineTooLong { line: 0, length: 51 }
+            },
+        ]);
+    }
+}
@@ -0,0 +1,186 @@
+// Inline markup for body text, e.g. "Hello <red>friend</red>".
+// Opening tags use the lowercase names of colors and effects, and closing tags
+// switch back to white text or no effect. Tags in uppercase are taken as raw command codes.
+// A backslash makes the next character literal, so "\<" is a plain "<".
+
+use crate::*;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarkupError {
+    UnterminatedTag { line: usize },
+    UnknownTag { line: usize, name: String },
+    InvalidClosingTag { line: usize, name: String },
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MarkupError::UnterminatedTag { line } => write!(f, "line {}: tag is never closed with '>'", line),
+            MarkupError::UnknownTag { line, name } => write!(f, "line {}: unknown tag <{}>", line, name),
+            MarkupError::InvalidClosingTag { line, name } => write!(f, "line {}: <{}> cannot be closed", line, name)
+        }
+    }
+}
+
+impl std::error::Error for MarkupError { }
+
+impl Body {
+    pub fn from_markup(markup: &str) -> Result<Self, MarkupError> {
+        let mut text_lines = vec![];
+        let mut text_commands = vec![];
+        for (line, line_markup) in markup.split('\n').enumerate() {
+            let (text, commands) = parse_markup_line(line_markup, line)?;
+            text_lines.push(text);
+            text_commands.extend(commands);
+        }
+        Ok(Body::new(text_lines, text_commands))
+    }
+
+    pub fn to_markup(&self) -> String {
+        let mut renderer = MarkupRenderer::default();
+        let last_line = self.text_lines().len().saturating_sub(1);
+        let lines: Vec<String> = (0..self.text_lines().len().max(1)).map(|line| {
+            let text = self.text_lines().get(line).map_or("", |text| text.as_str());
+            // Commands past the last line have nowhere else to go.
+            let commands = self.text_commands().iter().filter(|command| command.line().min(last_line) == line);
+            renderer.render_line(text, commands)
+        }).collect();
+        lines.join("\n")
+    }
+}
+
+/// Parses one line of markup into its plain text and the commands positioned on that line.
+pub fn parse_markup_line(markup: &str, line: usize) -> Result<(String, Vec<TextCommand>), MarkupError> {
+    let mut text = String::new();
+    let mut column = 0;
+    let mut commands = vec![];
+    let mut chars = markup.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    text.push(escaped);
+                    column += 1;
+                }
+            },
+            '<' => {
+                let mut tag = String::new();
+                loop {
+                    match chars.next() {
+                        Some('>') => break,
+                        Some(c) => tag.push(c),
+                        None => return Err(MarkupError::UnterminatedTag { line })
+                    }
+                }
+                let kind = parse_tag(&tag, line)?;
+                commands.push(TextCommand::from_kind(&kind, line, column));
+            },
+            _ => {
+                text.push(c);
+                column += 1;
+            }
+        }
+    }
+    Ok((text, commands))
+}
+
+fn parse_tag(tag: &str, line: usize) -> Result<TextCommandKind, MarkupError> {
+    let (closing, name) = match tag.strip_prefix('/') {
+        Some(name) => (true, name),
+        None => (false, tag)
+    };
+    if let Some(color) = TextColor::ALL.iter().find(|color| color.name() == name) {
+        Ok(TextCommandKind::Color(if closing { TextColor::White } else { *color }))
+    } else if let Some(effect) = TextEffect::ALL.iter().find(|effect| effect.name() == name) {
+        Ok(TextCommandKind::Effect(if closing { TextEffect::None } else { *effect }))
+    } else if closing {
+        Err(MarkupError::InvalidClosingTag { line, name: name.to_owned() })
+    } else if !name.is_empty() && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) {
+        Ok(TextCommandKind::from_code(name))
+    } else {
+        Err(MarkupError::UnknownTag { line, name: name.to_owned() })
+    }
+}
+
+/// Renders lines of a body one at a time, remembering which color and effect are still open
+/// so that switching back to the defaults can be written as a closing tag.
+#[derive(Debug, Default)]
+pub struct MarkupRenderer {
+    open_color: Option<TextColor>,
+    open_effect: Option<TextEffect>,
+}
+
+impl MarkupRenderer {
+    pub fn render_line<'a, I>(&mut self, text: &str, commands: I) -> String
+    where
+        I: IntoIterator<Item = &'a TextCommand>
+    {
+        let mut commands: Vec<&TextCommand> = commands.into_iter().collect();
+        commands.sort_by_key(|command| command.column());
+        let mut commands = commands.into_iter().peekable();
+
+        let mut markup = String::new();
+        for (column, c) in text.chars().enumerate() {
+            while let Some(command) = commands.next_if(|command| command.column() <= column) {
+                self.render_tag(&command.kind(), &mut markup);
+            }
+            if c == '<' || c == '\\' {
+                markup.push('\\');
+            }
+            markup.push(c);
+        }
+        for command in commands {
+            self.render_tag(&command.kind(), &mut markup);
+        }
+        markup
+    }
+
+    fn render_tag(&mut self, kind: &TextCommandKind, markup: &mut String) {
+        match kind {
+            TextCommandKind::Color(TextColor::White) if self.open_color.is_some() => {
+                markup.push_str(&format!("</{}>", self.open_color.take().unwrap().name()));
+            },
+            TextCommandKind::Color(color) => {
+                markup.push_str(&format!("<{}>", color.name()));
+                self.open_color = Some(*color).filter(|color| *color != TextColor::White);
+            },
+            TextCommandKind::Effect(TextEffect::None) if self.open_effect.is_some() => {
+                markup.push_str(&format!("</{}>", self.open_effect.take().unwrap().name()));
+            },
+            TextCommandKind::Effect(effect) => {
+                markup.push_str(&format!("<{}>", effect.name()));
+                self.open_effect = Some(*effect).filter(|effect| *effect != TextEffect::None);
+            },
+            TextCommandKind::Unknown(code) => markup.push_str(&format!("<{}>", code))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markup_round_trip() {
+        let markup = "Hello <red>friend</red>!\n<wave>1 \\< 2</wave> <EFOO>";
+        let body = Body::from_markup(markup).unwrap();
+        assert_eq!(body.text_lines(), ["Hello friend!", "1 < 2 "]);
+        let kinds: Vec<_> = body.text_commands().iter().map(|command| (command.kind(), command.line(), command.column())).collect();
+        assert_eq!(kinds, [
+            (TextCommandKind::Color(TextColor::Red), 0, 6),
+            (TextCommandKind::Color(TextColor::White), 0, 12),
+            (TextCommandKind::Effect(TextEffect::Wave), 1, 0),
+            (TextCommandKind::Effect(TextEffect::None), 1, 5),
+            (TextCommandKind::Unknown(String::from("EFOO")), 1, 6),
+        ]);
+        assert_eq!(body.to_markup(), markup);
+    }
+
+    #[test]
+    fn test_markup_errors() {
+        assert_eq!(Body::from_markup("<red").unwrap_err(), MarkupError::UnterminatedTag { line: 0 });
+        assert_eq!(Body::from_markup("\n<redd>").unwrap_err(), MarkupError::UnknownTag { line: 1, name: String::from("redd") });
+        assert_eq!(Body::from_markup("</EFOO>").unwrap_err(), MarkupError::InvalidClosingTag { line: 0, name: String::from("EFOO") });
+    }
+}
@@ -0,0 +1,181 @@
+use crate::*;
+use std::fmt;
+
+/// Somewhere both sides of a merge changed the same thing in different ways.
+/// The merged dialog keeps our side of every conflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeConflict {
+    Interchange { interchange: usize },
+    Body { interchange: usize },
+    Reply { interchange: usize, reply: usize },
+    AskAbout { askabout: usize },
+    AskAboutTopText,
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MergeConflict::Interchange { interchange } => write!(f, "interchange {} was removed on one side and changed on the other", interchange),
+            MergeConflict::Body { interchange } => write!(f, "interchange {} body", interchange),
+            MergeConflict::Reply { interchange, reply } => write!(f, "interchange {} reply {}", interchange, reply),
+            MergeConflict::AskAbout { askabout } => write!(f, "askabout {}", askabout),
+            MergeConflict::AskAboutTopText => write!(f, "askabout top text")
+        }
+    }
+}
+
+/// The result of a three-way merge.
+///
+/// Interchanges, replies and askabouts are matched up by index, since that's how replies and askabouts
+/// refer to interchanges. Removing something from the middle of a list therefore looks like every later
+/// element changing: it merges cleanly only if the other side left all of those elements alone, and
+/// otherwise each one the other side changed is a conflict. When one side removes elements from the end
+/// of a list and the other adds past them, the removed elements are put back and reported as conflicts,
+/// so that the additions keep their indices.
+#[derive(Debug, Clone)]
+pub struct DialogMerge {
+    pub dialog: Dialog,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl DialogMerge {
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+impl Dialog {
+    /// Three-way merges two edited versions of the same base dialog.
+    pub fn merge(base: &Dialog, ours: &Dialog, theirs: &Dialog) -> DialogMerge {
+        let mut conflicts = vec![];
+
+        let interchanges = merge_lists(&base.interchanges, &ours.interchanges, &theirs.interchanges, &mut conflicts,
+            |interchange| MergeConflict::Interchange { interchange },
+            |interchange, base, ours, theirs, conflicts| {
+                let body = merge_value(base.body(), ours.body(), theirs.body()).unwrap_or_else(|| {
+                    conflicts.push(MergeConflict::Body { interchange });
+                    ours.body().clone()
+                });
+                let replies = merge_lists(base.replies(), ours.replies(), theirs.replies(), conflicts,
+                    |reply| MergeConflict::Reply { interchange, reply },
+                    |reply, _, ours, _, conflicts| {
+                        conflicts.push(MergeConflict::Reply { interchange, reply });
+                        ours.clone()
+                    });
+                Interchange::new(body, replies)
+            });
+
+        let askabout_top_text = merge_value(&base.askabout_top_text, &ours.askabout_top_text, &theirs.askabout_top_text).unwrap_or_else(|| {
+            conflicts.push(MergeConflict::AskAboutTopText);
+            ours.askabout_top_text.clone()
+        });
+
+        let askabouts = merge_lists(&base.askabouts, &ours.askabouts, &theirs.askabouts, &mut conflicts,
+            |askabout| MergeConflict::AskAbout { askabout },
+            |askabout, _, ours, _, conflicts| {
+                conflicts.push(MergeConflict::AskAbout { askabout });
+                ours.clone()
+            });
+
+        DialogMerge {
+            dialog: Dialog::new(interchanges, askabouts, askabout_top_text),
+            conflicts,
+        }
+    }
+}
+
+// Returns None when both sides changed the value differently.
+fn merge_value<T>(base: &T, ours: &T, theirs: &T) -> Option<T>
+where
+    T: PartialEq + Clone
+{
+    if ours == theirs || theirs == base {
+        Some(ours.clone())
+    } else if ours == base {
+        Some(theirs.clone())
+    } else {
+        None
+    }
+}
+
+// Merges lists element by element. Elements that both sides changed are passed to merge_changed,
+// and elements that one side removed while the other changed them are conflicts that keep our side.
+fn merge_lists<T, C, M>(base: &[T], ours: &[T], theirs: &[T], conflicts: &mut Vec<MergeConflict>, conflict: C, mut merge_changed: M) -> Vec<T>
+where
+    T: PartialEq + Clone,
+    C: Fn(usize) -> MergeConflict,
+    M: FnMut(usize, &T, &T, &T, &mut Vec<MergeConflict>) -> T
+{
+    let length = base.len().max(ours.len()).max(theirs.len());
+    let mut merged: Vec<Option<T>> = Vec::with_capacity(length);
+    for i in 0..length {
+        let (b, o, t) = (base.get(i), ours.get(i), theirs.get(i));
+        let element = match merge_value(&b, &o, &t) {
+            Some(element) => element.cloned(),
+            None => match (b, o, t) {
+                (Some(b), Some(o), Some(t)) => Some(merge_changed(i, b, o, t, conflicts)),
+                _ => {
+                    conflicts.push(conflict(i));
+                    o.cloned()
+                }
+            }
+        };
+        merged.push(element);
+    }
+
+    // Everything is referred to by index, so a removal on one side can't be allowed to
+    // shift down what the other side added after it.
+    let kept = merged.iter().rposition(|element| element.is_some()).map_or(0, |last| last + 1);
+    merged.truncate(kept);
+    merged.into_iter().enumerate().map(|(i, element)| element.unwrap_or_else(|| {
+        conflicts.push(conflict(i));
+        theirs.get(i).or(base.get(i)).cloned().expect("one side should still have the element")
+    })).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interchange(text: &str, replies: &[&str]) -> Interchange {
+        Interchange::plain_text_with_replies(vec![text.to_owned()], replies.iter().map(|text| Reply::none(text.to_string())).collect())
+    }
+
+    #[test]
+    fn test_merge() {
+        let base = Dialog::new(vec![interchange("Hi", &["A", "B"]), interchange("Bye", &[])], vec![], String::new());
+        let ours = Dialog::new(vec![interchange("Hello", &["A", "B!"]), interchange("Bye", &[])], vec![], String::new());
+        let theirs = Dialog::new(vec![interchange("Hi", &["A", "B?", "C"]), interchange("Bye", &[]), interchange("New", &[])], vec![], String::new());
+
+        let merge = Dialog::merge(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts, [MergeConflict::Reply { interchange: 0, reply: 1 }]);
+        assert_eq!(merge.dialog.interchanges, [interchange("Hello", &["A", "B!", "C"]), interchange("Bye", &[]), interchange("New", &[])]);
+
+        let diff = base.diff(&merge.dialog);
+        assert_eq!(diff.changes().len(), 4);
+        assert_eq!(diff.to_string().lines().next(), Some("~ interchange 0 body"));
+    }
+
+    #[test]
+    fn test_merge_removals() {
+        let base = Dialog::new(vec![interchange("A", &[]), interchange("B", &[]), interchange("C", &[])], vec![], String::new());
+        let ours = Dialog::new(vec![interchange("A", &[]), interchange("C", &[])], vec![], String::new());
+        let theirs = Dialog::new(vec![interchange("A!", &[]), interchange("B", &[]), interchange("C", &[])], vec![], String::new());
+        let merge = Dialog::merge(&base, &ours, &theirs);
+        assert!(merge.is_clean());
+        assert_eq!(merge.dialog.interchanges, [interchange("A!", &[]), interchange("C", &[])]);
+
+        // Removing B shifts C down, which collides with their change to C.
+        let theirs = Dialog::new(vec![interchange("A", &[]), interchange("B", &[]), interchange("C!", &[])], vec![], String::new());
+        let merge = Dialog::merge(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts, [MergeConflict::Interchange { interchange: 2 }]);
+        assert_eq!(merge.dialog.interchanges, [interchange("A", &[]), interchange("C", &[])]);
+
+        // Their removal of C is undone so that our D stays at index 3.
+        let ours = Dialog::new(vec![interchange("A", &[]), interchange("B", &[]), interchange("C", &[]), interchange("D", &[])], vec![], String::new());
+        let theirs = Dialog::new(vec![interchange("A", &[]), interchange("B", &[])], vec![], String::new());
+        let merge = Dialog::merge(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts, [MergeConflict::Interchange { interchange: 2 }]);
+        assert_eq!(merge.dialog.interchanges, ours.interchanges);
+    }
+}
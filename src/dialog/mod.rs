@@ -2,22 +2,40 @@ pub mod ask_about;
 pub use ask_about::*;
 pub mod body;
 pub use body::*;
+pub mod composition;
+pub use composition::*;
+pub mod diff;
+pub use diff::*;
 pub mod interchange;
 pub use interchange::*;
+pub mod layout;
+pub use layout::*;
+pub mod localization;
+pub use localization::*;
+pub mod markup;
+pub use markup::*;
+pub mod merge;
+pub use merge::*;
 pub mod reply_function;
 pub use reply_function::*;
 pub mod reply;
 pub use reply::*;
+pub mod screenplay;
+pub use screenplay::*;
+pub mod shop;
+pub use shop::*;
+pub mod state_machine;
+pub use state_machine::*;
 pub mod text_command;
 pub use text_command::*;
 
 use crate::*;
-use serde::{Serialize, ser::SerializeStruct, Serializer, Deserialize, Deserializer, de::Visitor, de::SeqAccess, de};
+use serde::{Serialize, ser::{SerializeStruct, SerializeTuple}, Serializer, Deserialize, Deserializer, de::Visitor, de::SeqAccess, de};
 use std::fs::*;
 use std::io::Write;
 use std::path::Path;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Dialog {
     interchanges: Vec<Interchange>,
     askabouts: Vec<AskAbout>,
@@ -33,10 +51,61 @@ impl Dialog {
         }
     }
 
+    pub fn interchanges(&self) -> &[Interchange] {
+        &self.interchanges
+    }
+
     pub fn interchange_count(&self) -> usize {
         self.interchanges.len()
     }
 
+    pub fn askabouts(&self) -> &[AskAbout] {
+        &self.askabouts
+    }
+
+    pub fn askabout(&self, index: usize) -> Option<&AskAbout> {
+        self.askabouts.get(index)
+    }
+
+    pub fn askabout_mut(&mut self, index: usize) -> Option<&mut AskAbout> {
+        self.askabouts.get_mut(index)
+    }
+
+    pub fn find_askabout(&self, text: &str) -> Option<usize> {
+        self.askabouts.iter().position(|askabout| askabout.text == text)
+    }
+
+    /// Returns the index of the new askabout.
+    pub fn add_askabout(&mut self, askabout: AskAbout) -> usize {
+        self.askabouts.push(askabout);
+        self.askabouts.len() - 1
+    }
+
+    /// Askabouts after the removed one move down, so commands that target them by index need updating.
+    pub fn remove_askabout(&mut self, index: usize) -> AskAbout {
+        self.askabouts.remove(index)
+    }
+
+    pub fn askabout_top_text(&self) -> &str {
+        &self.askabout_top_text
+    }
+
+    pub fn set_askabout_top_text(&mut self, askabout_top_text: String) {
+        self.askabout_top_text = askabout_top_text;
+    }
+
+    /// Adds a reply to an interchange that makes one of this dialog's askabouts available when picked.
+    /// The dialog number is what the adventure calls this dialog, since the command could target any of them.
+    /// Returns the index of the new reply, or None if the interchange or askabout doesn't exist.
+    pub fn add_askabout_unlock(&mut self, interchange: usize, dialog_number: i32, askabout: usize, text: String, fnc: ReplyFunction) -> Option<usize> {
+        if askabout >= self.askabouts.len() {
+            return None;
+        }
+        let interchange = self.interchanges.get_mut(interchange)?;
+        interchange.add_reply(Reply::unlock_askabout(dialog_number, askabout as i32, text, fnc));
+        Some(interchange.replies().len() - 1)
+    }
+
     pub fn find_and_replace(&mut self, target: &str, replacement: &str) {
         for interchange in &mut self.interchanges {
             interchange.find_and_replace(target, replacement);
@@ -95,21 +164,32 @@ impl Dialog {
         self.interchanges.push(Interchange::placeholder(String::from("SUCCESS")));
     }
 
-    // TODO: Remove this crap.
-    // pub fn to_dia(&self, filename: &str) -> Result<(), Error> {
-    //     let file = File::create(format!("{}.dia", filename)).map_err(Error::InputOutput)?;
-    //     serde_blitz3d::to_writer(file, self).map_err(Error::Serde)
-    // }
+    /// Spreads the body across as many chained interchanges as it takes to fit the layout.
+    /// Every page except the last continues to the next one, and the last page gets the given replies.
+    /// Returns the index of the first page.
+    pub fn add_paginated_interchange(&mut self, layout: &BodyLayout, body: &Body, replies: Vec<Reply>, continue_text: &str) -> i32 {
+        let first_page = self.interchange_count() as i32;
+        let mut pages = layout.paginate(body);
+        let last_page = pages.pop().unwrap_or_default();
+        for page in pages {
+            let next_page = self.interchange_count() as i32 + 1;
+            self.interchanges.push(Interchange::new(page, vec![Reply::continue_to(next_page, continue_text.to_owned(), Cmd::none())]));
+        }
+        self.interchanges.push(Interchange::new(last_page, replies));
+        first_page
+    }
+
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let file = File::create(path).map_err(Error::InputOutput)?;
+        serde_blitz3d::to_writer(file, self).map_err(Error::Serde)
+    }
 
-    pub fn to_dia(&mut self, filename: &str) -> Result<(), Error> {
-        let file = File::create(format!("{}.dia", filename)).map_err(Error::InputOutput)?;
-        //file.write();
-        let mut writer = BlitzWriter::new(file);
-        writer.read_or_write(self);
-        Ok(())
+    #[deprecated(note = "adds \".dia\" to the file name; use to_file with the whole path instead")]
+    pub fn to_dia(&self, filename: &str) -> Result<(), Error> {
+        self.to_file(format!("{}.dia", filename))
     }
 
-    pub fn from_dia<P: AsRef<Path>>(path: P) -> Result<GameObject, Error> {
+    pub fn from_dia<P: AsRef<Path>>(path: P) -> Result<Dialog, Error> {
         let file = File::open(path).map_err(Error::InputOutput)?;
         serde_blitz3d::from_reader(file).map_err(Error::Serde)
     }
@@ -120,9 +200,24 @@ impl Serialize for Dialog {
     where
         S: Serializer
     {
-        let mut dialog = serializer.serialize_struct("Dialog", 3)?;
-        //dialog.serialize_i32(0);
-        dialog.serialize_field("interchanges", &self.interchanges)?;
+        if serializer.is_human_readable() {
+            let mut dialog = serializer.serialize_struct("Dialog", 3)?;
+            dialog.serialize_field("interchanges", &self.interchanges)?;
+            dialog.serialize_field("askabout_top_text", &self.askabout_top_text)?;
+            dialog.serialize_field("askabouts", &self.askabouts)?;
+            return dialog.end();
+        }
+        // Laid out the way DialogVisitor reads it, with each count before what it counts.
+        let mut dialog = serializer.serialize_tuple(self.interchanges.len() + self.askabouts.len() + 3)?;
+        dialog.serialize_element(&(self.interchanges.len() as i32))?;
+        for interchange in &self.interchanges {
+            dialog.serialize_element(interchange)?;
+        }
+        dialog.serialize_element(&(self.askabouts.len() as i32))?;
+        dialog.serialize_element(&self.askabout_top_text)?;
+        for askabout in &self.askabouts {
+            dialog.serialize_element(askabout)?;
+        }
         dialog.end()
     }
 }
@@ -132,10 +227,23 @@ impl<'de> Deserialize<'de> for Dialog {
     where
         D: Deserializer<'de>
     {
+        if deserializer.is_human_readable() {
+            let fields = DialogFields::deserialize(deserializer)?;
+            return Ok(Dialog::new(fields.interchanges, fields.askabouts, fields.askabout_top_text));
+        }
         deserializer.deserialize_struct("Dialog", &["interchanges", "askabouts", "askabout_top_text"], DialogVisitor)
     }
 }
 
+// How human-readable formats lay out a dialog, with names instead of counts.
+#[derive(Deserialize)]
+#[serde(rename = "Dialog")]
+struct DialogFields {
+    interchanges: Vec<Interchange>,
+    askabout_top_text: String,
+    askabouts: Vec<AskAbout>,
+}
+
 pub struct DialogVisitor;
 
 impl<'de> Visitor<'de> for DialogVisitor {
@@ -284,9 +392,81 @@ where
         self.read_or_write(&mut dialog.askabout_top_text);
         for askabout in askabouts {
             self.read_or_write(&mut askabout.text);
-            self.read_or_write(&mut askabout.active);
+            self.read_or_write(&mut askabout.active.0);
             self.read_or_write(&mut askabout.interchange);
-            self.read_or_write(&mut askabout.repeat);
+            self.read_or_write(&mut askabout.repeat.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dia_round_trip() {
+        let mut dialog = Dialog::default();
+        dialog.add_interchange(Interchange::new(
+            Body::new(vec![String::from("Hello"), String::from("there")], vec![TextCommand::at(String::from("CRED"), 1, 0)]),
+            vec![Reply::continue_to(1, String::from("Hi"), Cmd::activate(3)), Reply::end(0, String::from("Bye"), Cmd::none())]
+        ));
+        dialog.add_empty_interchange();
+        dialog.add_askabout(AskAbout::new(String::from("Stinkers"), AskAboutActive::ACTIVE, 1, AskAboutRepeat::UNLIMITED));
+        dialog.set_askabout_top_text(String::from("Ask about..."));
+
+        let mut bytes = vec![];
+        serde_blitz3d::to_writer(&mut bytes, &dialog).unwrap();
+        let mut written = vec![];
+        BlitzWriter::new(&mut written).read_or_write(&mut dialog.clone());
+        assert_eq!(bytes, written);
+
+        let read: Dialog = serde_blitz3d::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(read, dialog);
+    }
+
+    // The expected bytes are spelled out field by field as they appear in the game's .dia files, rather than produced by a writer.
+    #[test]
+    fn test_dia_matches_game_layout() {
+        let int = |bytes: &mut Vec<u8>, value: i32| bytes.extend(value.to_le_bytes());
+        let string = |bytes: &mut Vec<u8>, text: &str| {
+            bytes.extend((text.len() as i32).to_le_bytes());
+            bytes.extend(text.as_bytes());
+        };
+        let mut game_bytes = vec![];
+        int(&mut game_bytes, 1); // interchange count
+        int(&mut game_bytes, 2); // body line count
+        string(&mut game_bytes, "Hello");
+        string(&mut game_bytes, "there");
+        int(&mut game_bytes, 1); // text command count
+        string(&mut game_bytes, "CRED");
+        int(&mut game_bytes, 38);
+        int(&mut game_bytes, 1); // reply count
+        string(&mut game_bytes, "Bye");
+        for value in [1, 0, 10, 3, 0, 0, 0] { // reply function id and data, then the command
+            int(&mut game_bytes, value);
+        }
+        int(&mut game_bytes, 1); // askabout count
+        string(&mut game_bytes, "Ask about...");
+        string(&mut game_bytes, "Stinkers");
+        for value in [1, 0, -1] { // active, interchange, repeat
+            int(&mut game_bytes, value);
         }
+
+        let dialog = Dialog::new(
+            vec![Interchange::new(
+                Body::new(vec![String::from("Hello"), String::from("there")], vec![TextCommand::new(String::from("CRED"), 38)]),
+                vec![Reply::new(String::from("Bye"), ReplyFunction::new(1, 0), Cmd::new(10, 3, 0, 0, 0))]
+            )],
+            vec![AskAbout::new(String::from("Stinkers"), AskAboutActive(1), 0, AskAboutRepeat(-1))],
+            String::from("Ask about...")
+        );
+
+        let path = std::env::temp_dir().join(format!("waust-dialog-{}.dia", std::process::id()));
+        dialog.to_file(&path).unwrap();
+        let written = std::fs::read(&path).unwrap();
+        let read = Dialog::from_dia(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(written, game_bytes);
+        assert_eq!(read, dialog);
     }
-}
\ No newline at end of file
+}
@@ -1,7 +1,8 @@
 use crate::*;
 use serde::*;
+use std::fmt;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct Reply {
     text: String,
     fnc: ReplyFunction,
@@ -45,6 +46,10 @@ impl Reply {
         Self::new(text, ReplyFunction::check_for_item(fnc_id), cmd)
     }
 
+    pub fn unlock_askabout(dialog_number: i32, askabout: i32, text: String, fnc: ReplyFunction) -> Self {
+        Self::new(text, fnc, Cmd::set_askabout_active(dialog_number, askabout, AskAboutActive::ACTIVE))
+    }
+
     pub fn find_and_replace(&mut self, target: &str, replacement: &str) {
         self.text = self.text.replace(target, replacement);
     }
@@ -53,11 +58,42 @@ impl Reply {
         &self.text
     }
 
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+    }
+
     pub fn fnc(&self) -> ReplyFunction {
         self.fnc
     }
 
+    pub fn fnc_mut(&mut self) -> &mut ReplyFunction {
+        &mut self.fnc
+    }
+
     pub fn cmd(&self) -> Cmd {
         self.cmd
     }
-}
\ No newline at end of file
+}
+
+impl fmt::Display for Reply {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\"{}\" ({})", self.text, self.fnc)?;
+        if self.cmd != Cmd::none() {
+            write!(f, " [{}]", self.cmd)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlock_askabout() {
+        let reply = Reply::unlock_askabout(4, 2, "Tell me more".to_owned(), ReplyFunction::end(0));
+        assert_eq!(reply.cmd(), Cmd::new(22, 4, 2, 1, 0));
+        assert_eq!(reply.fnc(), ReplyFunction::end(0));
+        assert_eq!(reply.text(), "Tell me more");
+    }
+}
@@ -1,6 +1,7 @@
 use serde::*;
+use std::fmt;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub struct ReplyFunction {
     id: i32,
     data: i32
@@ -49,4 +50,33 @@ impl ReplyFunction {
     pub fn data(&self) -> i32 {
         self.data
     }
-}
\ No newline at end of file
+
+    /// The interchange that this function sends the conversation to, if it's one that does.
+    pub fn destination_interchange(&self) -> Option<i32> {
+        match self.id {
+            1 | 2 => Some(self.data),
+            _ => None
+        }
+    }
+
+    pub fn set_destination_interchange(&mut self, destination_interchange: i32) {
+        if self.destination_interchange().is_some() {
+            self.data = destination_interchange;
+        }
+    }
+}
+
+impl fmt::Display for ReplyFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.id {
+            0 => write!(f, "none"),
+            1 => write!(f, "end, next time start at {}", self.data),
+            2 => write!(f, "continue to {}", self.data),
+            3 => write!(f, "start askabout"),
+            4 => write!(f, "consume {} coins", self.data),
+            5 => write!(f, "consume item {}", self.data),
+            6 => write!(f, "check for item {}", self.data),
+            id => write!(f, "function {} ({})", id, self.data)
+        }
+    }
+}
@@ -0,0 +1,227 @@
+// Renders dialogs like a script for proofreading.
+// Each interchange is a section that replies link to, and text commands, reply functions
+// and commands are shown as annotations next to the text they belong to.
+
+use crate::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenplayFormat {
+    Markdown,
+    Html,
+}
+
+// A piece of a body line: either text to show or a text command tag from the markup.
+enum Span {
+    Text(char),
+    Tag(String),
+}
+
+fn body_spans(body: &Body) -> Vec<Vec<Span>> {
+    body.to_markup().split('\n').map(|line| {
+        let mut spans = vec![];
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => spans.extend(chars.next().map(Span::Text)),
+                '<' => spans.push(Span::Tag(chars.by_ref().take_while(|&c| c != '>').collect())),
+                _ => spans.push(Span::Text(c))
+            }
+        }
+        spans
+    }).collect()
+}
+
+fn interchange_anchor(interchange: i32) -> String {
+    format!("interchange-{}", interchange)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '#' | '|' => {
+                escaped.push('\\');
+                escaped.push(c);
+            },
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            _ => escaped.push(c)
+        }
+    }
+    escaped
+}
+
+impl Dialog {
+    pub fn to_screenplay(&self, title: &str, format: ScreenplayFormat) -> String {
+        match format {
+            ScreenplayFormat::Markdown => self.to_markdown(title),
+            ScreenplayFormat::Html => self.to_html(title)
+        }
+    }
+
+    pub fn to_markdown(&self, title: &str) -> String {
+        let mut markdown = format!("# {}\n", escape_markdown(title));
+        if !self.askabouts.is_empty() {
+            markdown.push_str("\n[Askabouts](#askabouts)\n");
+        }
+
+        for (i, interchange) in self.interchanges.iter().enumerate() {
+            markdown.push_str(&format!("\n<a id=\"{}\"></a>\n\n## Interchange {}\n\n", interchange_anchor(i as i32), i));
+            for spans in body_spans(interchange.body()) {
+                markdown.push('>');
+                if !spans.is_empty() {
+                    markdown.push(' ');
+                }
+                let mut text = String::new();
+                for span in spans {
+                    match span {
+                        Span::Text(c) => text.push(c),
+                        Span::Tag(tag) => {
+                            markdown.push_str(&escape_markdown(&text));
+                            text.clear();
+                            markdown.push_str(&format!("`<{}>`", tag));
+                        }
+                    }
+                }
+                markdown.push_str(&escape_markdown(&text));
+                markdown.push('\n');
+            }
+
+            if !interchange.replies().is_empty() {
+                markdown.push('\n');
+            }
+            for reply in interchange.replies() {
+                let text = escape_markdown(reply.text());
+                match reply.fnc().destination_interchange() {
+                    Some(destination) => markdown.push_str(&format!("- [{}](#{}) *— {}*", text, interchange_anchor(destination), reply.fnc())),
+                    None => markdown.push_str(&format!("- {} *— {}*", text, reply.fnc()))
+                }
+                if reply.cmd() != Cmd::none() {
+                    markdown.push_str(&format!(" **Cmd:** {}", reply.cmd()));
+                }
+                markdown.push('\n');
+            }
+        }
+
+        if !self.askabouts.is_empty() {
+            markdown.push_str("\n<a id=\"askabouts\"></a>\n\n## Askabouts\n\n");
+            if !self.askabout_top_text.is_empty() {
+                markdown.push_str(&format!("> {}\n\n", escape_markdown(&self.askabout_top_text)));
+            }
+            for askabout in &self.askabouts {
+                markdown.push_str(&format!("- [{}](#{}) *— {}, repeat {}*\n",
+                    escape_markdown(&askabout.text), interchange_anchor(askabout.interchange), askabout.active, askabout.repeat));
+            }
+        }
+        markdown
+    }
+
+    pub fn to_html(&self, title: &str) -> String {
+        let mut html = format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n", escape_html(title));
+        html.push_str("<style>\n\
+            body { font-family: sans-serif; max-width: 50em; margin: auto; }\n\
+            blockquote { font-family: monospace; white-space: pre; background: #f4f4f4; padding: 0.5em; }\n\
+            .tag, .note { color: #888; font-size: smaller; }\n\
+            .cmd { color: #a33; font-size: smaller; }\n\
+            </style>\n</head>\n<body>\n");
+        html.push_str(&format!("<h1>{}</h1>\n", escape_html(title)));
+        if !self.askabouts.is_empty() {
+            html.push_str("<p><a href=\"#askabouts\">Askabouts</a></p>\n");
+        }
+
+        for (i, interchange) in self.interchanges.iter().enumerate() {
+            html.push_str(&format!("<section id=\"{}\">\n<h2>Interchange {}</h2>\n<blockquote>", interchange_anchor(i as i32), i));
+            let lines: Vec<String> = body_spans(interchange.body()).into_iter().map(|spans| {
+                spans.into_iter().map(|span| match span {
+                    Span::Text(c) => escape_html(&c.to_string()),
+                    Span::Tag(tag) => format!("<span class=\"tag\">&lt;{}&gt;</span>", escape_html(&tag))
+                }).collect()
+            }).collect();
+            html.push_str(&lines.join("\n"));
+            html.push_str("</blockquote>\n");
+
+            if !interchange.replies().is_empty() {
+                html.push_str("<ul>\n");
+                for reply in interchange.replies() {
+                    let text = escape_html(reply.text());
+                    match reply.fnc().destination_interchange() {
+                        Some(destination) => html.push_str(&format!("<li><a href=\"#{}\">{}</a>", interchange_anchor(destination), text)),
+                        None => html.push_str(&format!("<li>{}", text))
+                    }
+                    html.push_str(&format!(" <span class=\"note\">{}</span>", escape_html(&reply.fnc().to_string())));
+                    if reply.cmd() != Cmd::none() {
+                        html.push_str(&format!(" <span class=\"cmd\">Cmd: {}</span>", escape_html(&reply.cmd().to_string())));
+                    }
+                    html.push_str("</li>\n");
+                }
+                html.push_str("</ul>\n");
+            }
+            html.push_str("</section>\n");
+        }
+
+        if !self.askabouts.is_empty() {
+            html.push_str("<section id=\"askabouts\">\n<h2>Askabouts</h2>\n");
+            if !self.askabout_top_text.is_empty() {
+                html.push_str(&format!("<blockquote>{}</blockquote>\n", escape_html(&self.askabout_top_text)));
+            }
+            html.push_str("<ul>\n");
+            for askabout in &self.askabouts {
+                html.push_str(&format!("<li><a href=\"#{}\">{}</a> <span class=\"note\">{}, repeat {}</span></li>\n",
+                    interchange_anchor(askabout.interchange), escape_html(&askabout.text), askabout.active, askabout.repeat));
+            }
+            html.push_str("</ul>\n</section>\n");
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dialog() -> Dialog {
+        let mut dialog = Dialog::default();
+        dialog.add_interchange(Interchange::new(Body::from_markup("Hi <red>A & B</red> \\<b>").unwrap(), vec![
+            Reply::continue_to(1, "Go <on>".to_owned(), Cmd::toggle(7)),
+            Reply::none("Stay *here*".to_owned()),
+        ]));
+        dialog.add_interchange(Interchange::new(Body::plain_text(vec!["Done".to_owned()]), vec![Reply::end(0, "Ok".to_owned(), Cmd::none())]));
+        dialog.set_askabout_top_text("Ask & see".to_owned());
+        dialog.add_askabout(AskAbout::new("Why?".to_owned(), AskAboutActive::master(3).unwrap(), 1, AskAboutRepeat::ONCE));
+        dialog
+    }
+
+    #[test]
+    fn test_markdown() {
+        let markdown = dialog().to_screenplay("Title", ScreenplayFormat::Markdown);
+        assert!(markdown.starts_with("# Title\n\n[Askabouts](#askabouts)\n"));
+        assert!(markdown.contains("<a id=\"interchange-0\"></a>\n\n## Interchange 0\n"));
+        assert!(markdown.contains("<a id=\"interchange-1\"></a>\n\n## Interchange 1\n"));
+        assert!(markdown.contains("> Hi `<red>`A &amp; B`</red>` &lt;b&gt;\n"));
+        assert!(markdown.contains("- [Go &lt;on&gt;](#interchange-1) *— continue to 1* **Cmd:** toggle 7\n"));
+        assert!(markdown.contains("- Stay \\*here\\* *— none*\n"));
+        assert!(markdown.contains("- [Ok](#interchange-0) *— end, next time start at 0*\n"));
+        assert!(markdown.ends_with("<a id=\"askabouts\"></a>\n\n## Askabouts\n\n> Ask &amp; see\n\n\
+            - [Why?](#interchange-1) *— master askabout 3, repeat once*\n"));
+    }
+
+    #[test]
+    fn test_html() {
+        let html = dialog().to_screenplay("A <b>", ScreenplayFormat::Html);
+        assert!(html.contains("<title>A &lt;b&gt;</title>"));
+        assert!(html.contains("<p><a href=\"#askabouts\">Askabouts</a></p>\n"));
+        assert!(html.contains("<section id=\"interchange-0\">\n<h2>Interchange 0</h2>\n\
+            <blockquote>Hi <span class=\"tag\">&lt;red&gt;</span>A &amp; B<span class=\"tag\">&lt;/red&gt;</span> &lt;b&gt;</blockquote>\n"));
+        assert!(html.contains("<li><a href=\"#interchange-1\">Go &lt;on&gt;</a> <span class=\"note\">continue to 1</span> <span class=\"cmd\">Cmd: toggle 7</span></li>\n"));
+        assert!(html.contains("<li>Stay *here* <span class=\"note\">none</span></li>\n"));
+        assert!(html.contains("<li><a href=\"#interchange-0\">Ok</a> <span class=\"note\">end, next time start at 0</span></li>\n"));
+        assert!(html.contains("<section id=\"askabouts\">\n<h2>Askabouts</h2>\n<blockquote>Ask &amp; see</blockquote>\n<ul>\n\
+            <li><a href=\"#interchange-1\">Why?</a> <span class=\"note\">master askabout 3, repeat once</span></li>\n"));
+    }
+}
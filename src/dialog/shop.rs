@@ -0,0 +1,124 @@
+use crate::*;
+
+#[derive(Debug, Clone)]
+pub struct ShopItem {
+    pub name: String,
+    pub price: i32,
+    /// Runs once the item has been paid for, e.g. to activate or spawn it.
+    pub cmd: Cmd,
+}
+
+impl ShopItem {
+    pub fn new(name: String, price: i32, cmd: Cmd) -> Self {
+        Self {
+            name,
+            price,
+            cmd
+        }
+    }
+}
+
+/// The text of a shop. "{item}" and "{price}" are replaced with the details of each item.
+#[derive(Debug, Clone)]
+pub struct ShopTemplate {
+    pub menu: Body,
+    pub item_reply: String,
+    pub leave_reply: String,
+    pub confirm: Body,
+    pub confirm_reply: String,
+    pub decline_reply: String,
+    pub insufficient_funds: Body,
+    pub purchased: Body,
+    pub back_reply: String,
+}
+
+impl Default for ShopTemplate {
+    fn default() -> Self {
+        Self {
+            menu: Body::one_liner(String::from("What would you like to buy?")),
+            item_reply: String::from("{item} ({price} coins)"),
+            leave_reply: String::from("Nothing, thanks."),
+            confirm: Body::one_liner(String::from("{item} costs {price} coins. Deal?")),
+            confirm_reply: String::from("Yes, please."),
+            decline_reply: String::from("No, thanks."),
+            insufficient_funds: Body::one_liner(String::from("You don't have enough coins!")),
+            purchased: Body::one_liner(String::from("Thank you for buying {item}!")),
+            back_reply: String::from("OK"),
+        }
+    }
+}
+
+impl ShopTemplate {
+    fn fill_text(text: &str, item: &ShopItem) -> String {
+        text.replace("{item}", &item.name).replace("{price}", &item.price.to_string())
+    }
+
+    fn fill_body(body: &Body, item: &ShopItem) -> Body {
+        let mut body = body.clone();
+        body.find_and_replace("{item}", &item.name);
+        body.find_and_replace("{price}", &item.price.to_string());
+        body
+    }
+}
+
+impl Dialog {
+    /// Adds a shop menu that loops back to itself after every purchase and returns its index.
+    /// Each item gets three interchanges: the purchase confirmation, then the insufficient funds branch,
+    /// then the purchase itself. Consuming coins skips ahead two interchanges when the player can pay
+    /// and one when they can't, the same as in an item consumption sequence.
+    pub fn add_shop(&mut self, items: &[ShopItem], template: &ShopTemplate) -> i32 {
+        let menu = self.interchange_count() as i32;
+        let mut menu_replies = Vec::with_capacity(items.len() + 1);
+        for (i, item) in items.iter().enumerate() {
+            let confirmation = menu + 1 + 3 * i as i32;
+            menu_replies.push(Reply::continue_to(confirmation, ShopTemplate::fill_text(&template.item_reply, item), Cmd::none()));
+        }
+        menu_replies.push(Reply::end(menu, template.leave_reply.clone(), Cmd::none()));
+        self.interchanges.push(Interchange::new(template.menu.clone(), menu_replies));
+
+        for item in items {
+            self.interchanges.push(Interchange::new(ShopTemplate::fill_body(&template.confirm, item), vec![
+                Reply::consume_coins(item.price, ShopTemplate::fill_text(&template.confirm_reply, item), Cmd::none()),
+                Reply::continue_to(menu, ShopTemplate::fill_text(&template.decline_reply, item), Cmd::none()),
+            ]));
+            self.interchanges.push(Interchange::new(ShopTemplate::fill_body(&template.insufficient_funds, item), vec![
+                Reply::continue_to(menu, ShopTemplate::fill_text(&template.back_reply, item), Cmd::none()),
+            ]));
+            // The command goes here rather than on the confirmation reply so that it only runs once the coins are gone.
+            self.interchanges.push(Interchange::new(ShopTemplate::fill_body(&template.purchased, item), vec![
+                Reply::continue_to(menu, ShopTemplate::fill_text(&template.back_reply, item), item.cmd),
+            ]));
+        }
+        menu
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_shop() {
+        let mut dialog = Dialog::default();
+        dialog.add_empty_interchange();
+        let items = [ShopItem::new(String::from("Hat"), 5, Cmd::activate(10)), ShopItem::new(String::from("Boots"), 12, Cmd::activate(11))];
+        let menu = dialog.add_shop(&items, &ShopTemplate::default());
+        assert_eq!(menu, 1);
+        assert_eq!(dialog.interchange_count(), 8);
+
+        let functions = |index: usize| -> Vec<_> { dialog.interchanges()[index].replies().iter().map(|reply| reply.fnc()).collect() };
+        assert_eq!(functions(1), vec![ReplyFunction::continue_to(2), ReplyFunction::continue_to(5), ReplyFunction::end(1)]);
+        assert_eq!(dialog.interchanges()[1].replies()[1].text(), "Boots (12 coins)");
+        for (i, item) in items.iter().enumerate() {
+            let confirmation = 2 + 3 * i;
+            // Consuming coins goes on one interchange when the player can't pay and two when they can.
+            assert_eq!(functions(confirmation), vec![ReplyFunction::consume_coins(item.price), ReplyFunction::continue_to(1)]);
+            assert_eq!(functions(confirmation + 1), vec![ReplyFunction::continue_to(1)]);
+            assert_eq!(functions(confirmation + 2), vec![ReplyFunction::continue_to(1)]);
+            assert_eq!(dialog.interchanges()[confirmation + 2].body_text_lines()[0], format!("Thank you for buying {}!", item.name));
+
+            let cmds: Vec<_> = (confirmation..confirmation + 3).flat_map(|index| dialog.interchanges()[index].replies()).map(|reply| reply.cmd()).collect();
+            assert_eq!(cmds, vec![Cmd::none(), Cmd::none(), Cmd::none(), item.cmd]);
+        }
+    }
+}
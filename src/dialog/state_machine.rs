@@ -0,0 +1,298 @@
+use crate::*;
+use std::collections::VecDeque;
+use std::fmt;
+
+/// Accepting and rejecting states are sinks, which can only leave the state machine through exits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateKind {
+    Intermediate,
+    Accepting,
+    Rejecting,
+}
+
+impl StateKind {
+    pub fn is_sink(&self) -> bool {
+        *self != StateKind::Intermediate
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateMachineError {
+    MissingStart { start: usize },
+    MissingTarget { state: usize, target: usize },
+    SinkHasTransition { state: usize, target: usize },
+}
+
+impl fmt::Display for StateMachineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StateMachineError::MissingStart { start } => write!(f, "start state {} doesn't exist", start),
+            StateMachineError::MissingTarget { state, target } => write!(f, "state {} has a transition to missing state {}", state, target),
+            StateMachineError::SinkHasTransition { state, target } => write!(f, "state {} is a sink but has a transition to state {}", state, target)
+        }
+    }
+}
+
+impl std::error::Error for StateMachineError { }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionTarget {
+    State(usize),
+    /// Leaves the state machine with the given reply function, e.g. to end the conversation.
+    Exit(ReplyFunction),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transition {
+    pub text: String,
+    pub target: TransitionTarget,
+    pub cmd: Cmd,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct State {
+    pub body: Body,
+    pub kind: StateKind,
+    pub transitions: Vec<Transition>,
+}
+
+/// A finite state machine where each state is shown as an interchange and each transition is a reply.
+#[derive(Debug, Clone, Default)]
+pub struct StateMachine {
+    states: Vec<State>,
+}
+
+impl StateMachine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_state(&mut self, body: Body, kind: StateKind) -> usize {
+        self.states.push(State {
+            body,
+            kind,
+            transitions: vec![],
+        });
+        self.states.len() - 1
+    }
+
+    pub fn add_transition(&mut self, from: usize, text: String, to: usize, cmd: Cmd) {
+        self.states[from].transitions.push(Transition {
+            text,
+            target: TransitionTarget::State(to),
+            cmd,
+        });
+    }
+
+    pub fn add_exit(&mut self, from: usize, text: String, fnc: ReplyFunction, cmd: Cmd) {
+        self.states[from].transitions.push(Transition {
+            text,
+            target: TransitionTarget::Exit(fnc),
+            cmd,
+        });
+    }
+
+    pub fn states(&self) -> &[State] {
+        &self.states
+    }
+
+    /// Checks that the start and every transition lead to states that exist, and that sinks only have exits.
+    pub fn validate(&self, start: usize) -> Result<(), StateMachineError> {
+        if start >= self.states.len() {
+            return Err(StateMachineError::MissingStart { start });
+        }
+        for (index, state) in self.states.iter().enumerate() {
+            for transition in &state.transitions {
+                if let TransitionTarget::State(target) = transition.target {
+                    if target >= self.states.len() {
+                        return Err(StateMachineError::MissingTarget { state: index, target });
+                    }
+                    if state.kind.is_sink() {
+                        return Err(StateMachineError::SinkHasTransition { state: index, target });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Merges states that behave the same and drops states that can't be reached from the start.
+    /// The start state becomes state 0. Also returns the new index of every old state that was kept.
+    pub fn minimized(&self, start: usize) -> Result<(StateMachine, Vec<Option<usize>>), StateMachineError> {
+        self.validate(start)?;
+        // Breadth-first order, so the start comes first and the layout follows the flow of the conversation.
+        let mut order = vec![];
+        let mut reachable = vec![false; self.states.len()];
+        let mut queue = VecDeque::from([start]);
+        reachable[start] = true;
+        while let Some(state) = queue.pop_front() {
+            order.push(state);
+            for transition in &self.states[state].transitions {
+                if let TransitionTarget::State(target) = transition.target {
+                    if !reachable[target] {
+                        reachable[target] = true;
+                        queue.push_back(target);
+                    }
+                }
+            }
+        }
+
+        // Start by telling states apart by everything except where their transitions lead,
+        // then keep splitting classes by which classes their transitions lead to.
+        let mut class = vec![0; self.states.len()];
+        let mut class_count = classify(&order, &mut class, |state| {
+            let state = &self.states[state];
+            let transitions: Vec<_> = state.transitions.iter().map(|transition| match transition.target {
+                TransitionTarget::State(_) => (&transition.text, transition.cmd, None),
+                TransitionTarget::Exit(fnc) => (&transition.text, transition.cmd, Some(fnc))
+            }).collect();
+            (state.kind, &state.body, transitions)
+        });
+        loop {
+            let previous = class.clone();
+            let new_count = classify(&order, &mut class, |state| {
+                let targets: Vec<_> = self.states[state].transitions.iter().map(|transition| match transition.target {
+                    TransitionTarget::State(target) => Some(previous[target]),
+                    TransitionTarget::Exit(_) => None
+                }).collect();
+                (previous[state], targets)
+            });
+            if new_count == class_count {
+                break;
+            }
+            class_count = new_count;
+        }
+
+        let mut minimized = StateMachine::new();
+        let mut representatives: Vec<usize> = vec![];
+        for &state in &order {
+            if class[state] == representatives.len() {
+                representatives.push(state);
+            }
+        }
+        for &representative in &representatives {
+            let state = &self.states[representative];
+            minimized.states.push(State {
+                body: state.body.clone(),
+                kind: state.kind,
+                transitions: state.transitions.iter().map(|transition| Transition {
+                    target: match transition.target {
+                        TransitionTarget::State(target) => TransitionTarget::State(class[target]),
+                        exit => exit
+                    },
+                    ..transition.clone()
+                }).collect(),
+            });
+        }
+        let mapping = (0..self.states.len()).map(|state| Some(class[state]).filter(|_| reachable[state])).collect();
+        Ok((minimized, mapping))
+    }
+}
+
+// Numbers the classes in order of first appearance and returns how many there are.
+fn classify<K, F>(order: &[usize], class: &mut [usize], mut key: F) -> usize
+where
+    K: PartialEq,
+    F: FnMut(usize) -> K
+{
+    let mut keys: Vec<K> = vec![];
+    for &state in order {
+        let state_key = key(state);
+        class[state] = match keys.iter().position(|key| *key == state_key) {
+            Some(existing) => existing,
+            None => {
+                keys.push(state_key);
+                keys.len() - 1
+            }
+        };
+    }
+    keys.len()
+}
+
+impl Dialog {
+    /// Minimizes the state machine and adds one interchange per remaining state, starting with the start state.
+    /// Returns the interchange that each state ended up as, or None for states that can't be reached.
+    pub fn add_state_machine(&mut self, machine: &StateMachine, start: usize) -> Result<Vec<Option<i32>>, StateMachineError> {
+        let first = self.interchange_count() as i32;
+        let (minimized, mapping) = machine.minimized(start)?;
+        for state in minimized.states {
+            let replies = state.transitions.into_iter().map(|transition| {
+                let fnc = match transition.target {
+                    TransitionTarget::State(target) => ReplyFunction::continue_to(first + target as i32),
+                    TransitionTarget::Exit(fnc) => fnc
+                };
+                Reply::new(transition.text, fnc, transition.cmd)
+            }).collect();
+            self.interchanges.push(Interchange::new(state.body, replies));
+        }
+        Ok(mapping.into_iter().map(|state| state.map(|state| first + state as i32)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_machine_is_minimized() {
+        // A two-digit password where each wrong path got its own copy of the failure states.
+        let body = || Body::one_liner(String::from("..."));
+        let mut machine = StateMachine::new();
+        let start = machine.add_state(body(), StateKind::Intermediate);
+        let first_right = machine.add_state(body(), StateKind::Intermediate);
+        let first_wrong_a = machine.add_state(body(), StateKind::Intermediate);
+        let first_wrong_b = machine.add_state(body(), StateKind::Intermediate);
+        let success = machine.add_state(Body::one_liner(String::from("SUCCESS")), StateKind::Accepting);
+        let failure_a = machine.add_state(Body::one_liner(String::from("FAILURE")), StateKind::Rejecting);
+        let failure_b = machine.add_state(Body::one_liner(String::from("FAILURE")), StateKind::Rejecting);
+        let unreachable = machine.add_state(body(), StateKind::Intermediate);
+        for (from, targets) in [
+            (start, [first_wrong_a, first_right, first_wrong_b]),
+            (first_right, [failure_a, failure_b, success]),
+            (first_wrong_a, [failure_a; 3]),
+            (first_wrong_b, [failure_b; 3]),
+            (unreachable, [start; 3]),
+        ] {
+            for (option, target) in targets.into_iter().enumerate() {
+                machine.add_transition(from, option.to_string(), target, Cmd::none());
+            }
+        }
+        machine.add_exit(success, String::from("Yay"), ReplyFunction::end(0), Cmd::activate(5));
+
+        let (minimized, mapping) = machine.minimized(start).unwrap();
+        assert_eq!(minimized.states().len(), 5);
+        assert_eq!(mapping[first_wrong_a], mapping[first_wrong_b]);
+        assert_eq!(mapping[failure_a], mapping[failure_b]);
+        assert_eq!(mapping[unreachable], None);
+
+        let mut dialog = Dialog::default();
+        dialog.add_empty_interchange();
+        let interchanges = dialog.add_state_machine(&machine, start).unwrap();
+        assert_eq!(interchanges[start], Some(1));
+        assert_eq!(interchanges[failure_b], interchanges[failure_a]);
+        assert_eq!(interchanges[unreachable], None);
+        assert_eq!(dialog.interchange_count(), 6);
+        assert_eq!(dialog.interchanges[1].replies()[0].fnc(), ReplyFunction::continue_to(2));
+        assert_eq!(dialog.interchanges[1].replies()[1].fnc(), ReplyFunction::continue_to(3));
+        assert_eq!(dialog.interchanges[1].replies()[2].fnc(), ReplyFunction::continue_to(2));
+    }
+
+    #[test]
+    fn test_invalid_machines() {
+        let mut machine = StateMachine::new();
+        let start = machine.add_state(Body::one_liner(String::from("Guess")), StateKind::Intermediate);
+        let success = machine.add_state(Body::one_liner(String::from("SUCCESS")), StateKind::Accepting);
+        machine.add_transition(start, String::from("1"), success, Cmd::none());
+        assert!(machine.validate(start).is_ok());
+        assert_eq!(machine.minimized(2).unwrap_err(), StateMachineError::MissingStart { start: 2 });
+
+        machine.add_transition(success, String::from("Again"), start, Cmd::none());
+        assert_eq!(machine.validate(start), Err(StateMachineError::SinkHasTransition { state: success, target: start }));
+        let mut dialog = Dialog::default();
+        assert!(dialog.add_state_machine(&machine, start).is_err());
+        assert_eq!(dialog.interchange_count(), 0);
+
+        machine.add_transition(start, String::from("2"), 5, Cmd::none());
+        assert_eq!(machine.validate(start), Err(StateMachineError::MissingTarget { state: start, target: 5 }));
+    }
+}
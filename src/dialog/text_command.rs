@@ -1,6 +1,7 @@
+use crate::*;
 use serde::*;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct TextCommand {
     name: String,
     position: i32
@@ -14,6 +15,11 @@ impl TextCommand {
         }
     }
 
+    /// Positions count characters across a grid that is always as wide as the game's dialog box.
+    pub fn at(name: String, line: usize, column: usize) -> Self {
+        Self::new(name, (line * BODY_LINE_LENGTH + column) as i32)
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -21,4 +27,156 @@ impl TextCommand {
     pub fn position(&self) -> i32 {
         self.position
     }
-}
\ No newline at end of file
+
+    pub fn line(&self) -> usize {
+        self.position.max(0) as usize / BODY_LINE_LENGTH
+    }
+
+    pub fn column(&self) -> usize {
+        self.position.max(0) as usize % BODY_LINE_LENGTH
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextColor {
+    White,
+    Grey,
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Rainbow,
+}
+
+impl TextColor {
+    pub const ALL: [Self; 9] = [
+        Self::White,
+        Self::Grey,
+        Self::Red,
+        Self::Orange,
+        Self::Yellow,
+        Self::Green,
+        Self::Blue,
+        Self::Purple,
+        Self::Rainbow,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::White => "CWHI",
+            Self::Grey => "CGRY",
+            Self::Red => "CRED",
+            Self::Orange => "CORA",
+            Self::Yellow => "CYEL",
+            Self::Green => "CGRE",
+            Self::Blue => "CBLU",
+            Self::Purple => "CPUR",
+            Self::Rainbow => "CRAI",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::White => "white",
+            Self::Grey => "grey",
+            Self::Red => "red",
+            Self::Orange => "orange",
+            Self::Yellow => "yellow",
+            Self::Green => "green",
+            Self::Blue => "blue",
+            Self::Purple => "purple",
+            Self::Rainbow => "rainbow",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEffect {
+    None,
+    Jitter,
+    Wave,
+    Bounce,
+    Zoom,
+    Circle,
+    UpDown,
+    LeftRight,
+}
+
+impl TextEffect {
+    pub const ALL: [Self; 8] = [
+        Self::None,
+        Self::Jitter,
+        Self::Wave,
+        Self::Bounce,
+        Self::Zoom,
+        Self::Circle,
+        Self::UpDown,
+        Self::LeftRight,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::None => "ENON",
+            Self::Jitter => "EJIT",
+            Self::Wave => "EWAV",
+            Self::Bounce => "EBOU",
+            Self::Zoom => "EZOO",
+            Self::Circle => "ECIR",
+            Self::UpDown => "EUPD",
+            Self::LeftRight => "ELER",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Jitter => "jitter",
+            Self::Wave => "wave",
+            Self::Bounce => "bounce",
+            Self::Zoom => "zoom",
+            Self::Circle => "circle",
+            Self::UpDown => "updown",
+            Self::LeftRight => "leftright",
+        }
+    }
+}
+
+/// What a text command does, with commands this crate doesn't know about kept by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextCommandKind {
+    Color(TextColor),
+    Effect(TextEffect),
+    Unknown(String),
+}
+
+impl TextCommandKind {
+    pub fn from_code(code: &str) -> Self {
+        if let Some(color) = TextColor::ALL.iter().find(|color| color.code() == code) {
+            Self::Color(*color)
+        } else if let Some(effect) = TextEffect::ALL.iter().find(|effect| effect.code() == code) {
+            Self::Effect(*effect)
+        } else {
+            Self::Unknown(code.to_owned())
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        match self {
+            Self::Color(color) => color.code(),
+            Self::Effect(effect) => effect.code(),
+            Self::Unknown(code) => code,
+        }
+    }
+}
+
+impl TextCommand {
+    pub fn from_kind(kind: &TextCommandKind, line: usize, column: usize) -> Self {
+        Self::at(kind.code().to_owned(), line, column)
+    }
+
+    pub fn kind(&self) -> TextCommandKind {
+        TextCommandKind::from_code(&self.name)
+    }
+}
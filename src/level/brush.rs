@@ -0,0 +1,229 @@
+use std::collections::VecDeque;
+
+use crate::*;
+
+/// The tile properties to paint. Those left as None aren't touched.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TileBrush {
+    pub texture: Option<i32>,
+    pub rotation: Option<i32>,
+    pub side_texture: Option<i32>,
+    pub side_rotation: Option<i32>,
+    pub random: Option<f32>,
+    pub height: Option<f32>,
+    pub extrusion: Option<f32>,
+    pub rounding: Option<Rounding>,
+    pub edge_random: Option<EdgeRandom>,
+    pub logic: Option<LevelTileLogic>,
+    pub water_texture: Option<i32>,
+    pub water_rotation: Option<i32>,
+    pub water_height: Option<f32>,
+    pub water_turbulence: Option<f32>,
+}
+
+impl TileBrush {
+    pub fn apply(&self, tile: &mut LevelTileLatest, water: &mut WaterTile) {
+        let beta = tile.beta_mut();
+        if let Some(texture) = self.texture { beta.set_texture(texture) }
+        if let Some(rotation) = self.rotation { beta.set_rotation(rotation) }
+        if let Some(side_texture) = self.side_texture { beta.set_side_texture(side_texture) }
+        if let Some(side_rotation) = self.side_rotation { beta.set_side_rotation(side_rotation) }
+        if let Some(random) = self.random { beta.set_random(random) }
+        if let Some(height) = self.height { beta.set_height(height) }
+        if let Some(extrusion) = self.extrusion { beta.set_extrusion(extrusion) }
+        if let Some(rounding) = self.rounding { beta.set_rounding(rounding) }
+        if let Some(edge_random) = self.edge_random { beta.set_edge_random(edge_random) }
+        if let Some(logic) = self.logic { tile.set_logic(logic) }
+        if let Some(texture) = self.water_texture { water.texture = texture }
+        if let Some(rotation) = self.water_rotation { water.rotation = rotation }
+        if let Some(height) = self.water_height { water.height = height }
+        if let Some(turbulence) = self.water_turbulence { water.turbulence = turbulence }
+    }
+}
+
+/// A pattern of brushes to paint in one go. Cells without a brush are left alone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stamp {
+    width: i32,
+    height: i32,
+    brushes: Vec<Option<TileBrush>>,
+}
+
+impl Stamp {
+    pub fn from_fn<F>(width: i32, height: i32, mut f: F) -> Self
+    where
+        F: FnMut(i32, i32) -> Option<TileBrush>
+    {
+        Self {
+            width,
+            height,
+            brushes: (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| f(x, y)).collect(),
+        }
+    }
+
+    /// One string per row and one character per cell, looked up in the legend.
+    /// Characters missing from the legend leave their cell alone.
+    pub fn from_text(rows: &[&str], legend: &[(char, TileBrush)]) -> Self {
+        let width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+        Self::from_fn(width as i32, rows.len() as i32, |x, y| {
+            let c = rows[y as usize].chars().nth(x as usize)?;
+            legend.iter().find(|(key, _)| *key == c).map(|(_, brush)| *brush)
+        })
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> Option<&TileBrush> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        self.brushes[(y * self.width + x) as usize].as_ref()
+    }
+}
+
+// Every brush clips to the terrain and returns how many tiles it painted.
+impl Terrain<LevelTileLatest> {
+    pub fn paint(&mut self, x: i32, y: i32, brush: &TileBrush) -> bool {
+        match self.cell_mut(x, y) {
+            Some((tile, water)) => {
+                brush.apply(tile, water);
+                true
+            },
+            None => false
+        }
+    }
+
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32, brush: &TileBrush) -> usize {
+        let mut painted = 0;
+        for y in y..y + height {
+            for x in x..x + width {
+                painted += usize::from(self.paint(x, y, brush));
+            }
+        }
+        painted
+    }
+
+    /// Paints every tile the line from one tile to the other passes through, ends included.
+    pub fn line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, brush: &TileBrush) -> usize {
+        // Bresenham's algorithm.
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (step_x, step_y) = ((x1 - x0).signum(), (y1 - y0).signum());
+        let (mut x, mut y, mut error) = (x0, y0, dx + dy);
+        let mut painted = 0;
+        loop {
+            painted += usize::from(self.paint(x, y, brush));
+            if x == x1 && y == y1 {
+                return painted;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Paints every tile whose center is within the radius of the given tile's center.
+    pub fn circle(&mut self, center_x: i32, center_y: i32, radius: f32, brush: &TileBrush) -> usize {
+        let reach = radius.max(0.0).floor() as i32;
+        let mut painted = 0;
+        for y in center_y - reach..=center_y + reach {
+            for x in center_x - reach..=center_x + reach {
+                let (dx, dy) = ((x - center_x) as f32, (y - center_y) as f32);
+                if dx * dx + dy * dy <= radius * radius {
+                    painted += usize::from(self.paint(x, y, brush));
+                }
+            }
+        }
+        painted
+    }
+
+    /// Paints the region of tiles matching the predicate that's connected to the starting tile.
+    /// Matching is decided before anything is painted, so the brush can leave tiles still matching.
+    pub fn flood_fill<P>(&mut self, x: i32, y: i32, neighborhood: Neighborhood, mut predicate: P, brush: &TileBrush) -> usize
+    where
+        P: FnMut(&LevelTileLatest, &WaterTile) -> bool
+    {
+        let mut region = vec![false; self.level_tiles().len()];
+        let mut queue = VecDeque::new();
+        if let Some((tile, water)) = self.cell(x, y) {
+            if predicate(tile, water) {
+                region[self.index_of(x, y).expect("the cell should be inside the terrain")] = true;
+                queue.push_back((x, y));
+            }
+        }
+        while let Some((x, y)) = queue.pop_front() {
+            for (x, y) in self.neighbors(x, y, neighborhood).collect::<Vec<_>>() {
+                let i = self.index_of(x, y).expect("neighbors should be inside the terrain");
+                if !region[i] && predicate(&self.level_tiles()[i], &self.water_tiles()[i]) {
+                    region[i] = true;
+                    queue.push_back((x, y));
+                }
+            }
+        }
+
+        let mut painted = 0;
+        for (i, _) in region.iter().enumerate().filter(|(_, &inside)| inside) {
+            let (x, y) = self.coordinates(i);
+            painted += usize::from(self.paint(x, y, brush));
+        }
+        painted
+    }
+
+    /// Paints the stamp with its top left corner at the given tile.
+    pub fn stamp(&mut self, x: i32, y: i32, stamp: &Stamp) -> usize {
+        let mut painted = 0;
+        for stamp_y in 0..stamp.height() {
+            for stamp_x in 0..stamp.width() {
+                if let Some(brush) = stamp.get(stamp_x, stamp_y) {
+                    painted += usize::from(self.paint(x + stamp_x, y + stamp_y, brush));
+                }
+            }
+        }
+        painted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logic_map(terrain: &Terrain<LevelTileLatest>) -> Vec<String> {
+        terrain.rows()
+            .map(|row| row.iter().map(|tile| if tile.logic() == LevelTileLogic::FLOOR { '.' } else { '#' }).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_brushes() {
+        let wall = TileBrush { logic: Some(LevelTileLogic::WALL), height: Some(1.0), ..Default::default() };
+        let mut terrain: Terrain<LevelTileLatest> = Terrain::new(5, 5);
+        assert_eq!(terrain.line(0, 0, 4, 2, &wall), 5);
+        assert_eq!(logic_map(&terrain), ["#....", ".##..", "...##", ".....", "....."]);
+        assert_eq!(terrain[(1, 1)].beta().height(), 1.0);
+
+        let mut terrain: Terrain<LevelTileLatest> = Terrain::new(5, 5);
+        assert_eq!(terrain.circle(2, 2, 1.5, &wall), 9);
+        assert_eq!(terrain.fill_rect(3, 3, 4, 4, &wall), 4);
+        let water = TileBrush { logic: Some(LevelTileLogic::WATER), water_height: Some(-0.5), ..Default::default() };
+        let floor_count = terrain.flood_fill(0, 0, Neighborhood::Orthogonal, |tile, _| tile.logic() == LevelTileLogic::FLOOR, &water);
+        assert_eq!(floor_count, 25 - 9 - 3);
+        assert_eq!(terrain.water(0, 4).unwrap().height, -0.5);
+        assert_eq!(terrain.flood_fill(-1, 0, Neighborhood::All, |_, _| true, &water), 0);
+
+        let mut terrain: Terrain<LevelTileLatest> = Terrain::new(3, 2);
+        let stamp = Stamp::from_text(&["#.", " #"], &[('#', wall), ('.', TileBrush::default())]);
+        assert_eq!(terrain.stamp(1, 0, &stamp), 3);
+        assert_eq!(logic_map(&terrain), [".#.", "..#"]);
+    }
+}
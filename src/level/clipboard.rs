@@ -0,0 +1,264 @@
+use std::{collections::BTreeMap, fmt, ops::Range};
+
+use crate::*;
+
+/// The ways an object can refer to other objects by ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectReference {
+    Linked,
+    LinkBack,
+    Parent,
+    Child,
+    /// The target of a command button's Cmd.
+    Command,
+}
+
+impl ObjectReference {
+    pub const ALL: [Self; 5] = [Self::Linked, Self::LinkBack, Self::Parent, Self::Child, Self::Command];
+
+    /// None if the object doesn't refer to anything this way.
+    pub fn get(self, object: &GameObject) -> Option<i32> {
+        let id = match self {
+            ObjectReference::Linked => object.linked(),
+            ObjectReference::LinkBack => object.link_back(),
+            ObjectReference::Parent => object.ancient_object().parent(),
+            ObjectReference::Child => object.ancient_object().child(),
+            ObjectReference::Command => object.command()?.target_id()?
+        };
+        Some(id).filter(|&id| id >= 0)
+    }
+
+    pub fn set(self, object: &mut GameObject, id: i32) {
+        match self {
+            ObjectReference::Linked => object.set_linked(id),
+            ObjectReference::LinkBack => object.set_link_back(id),
+            ObjectReference::Parent => object.ancient_object_mut().set_parent(id),
+            ObjectReference::Child => object.ancient_object_mut().set_child(id),
+            ObjectReference::Command => if let Some(mut cmd) = object.command() {
+                cmd.set_target_id(id);
+                object.set_command(cmd);
+            }
+        }
+    }
+}
+
+impl fmt::Display for ObjectReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjectReference::Linked => write!(f, "linked"),
+            ObjectReference::LinkBack => write!(f, "link back"),
+            ObjectReference::Parent => write!(f, "parent"),
+            ObjectReference::Child => write!(f, "child"),
+            ObjectReference::Command => write!(f, "command target")
+        }
+    }
+}
+
+/// A reference to an ID that none of the copied objects have, which is left as it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DanglingReference {
+    /// The index of the object that refers to the ID.
+    pub object: usize,
+    pub reference: ObjectReference,
+    pub id: i32,
+}
+
+impl fmt::Display for DanglingReference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "object {}: {} {} is outside the region", self.object, self.reference, self.id)
+    }
+}
+
+/// A rectangle of terrain copied out of a level along with the objects standing on it.
+/// Positions are relative to the rectangle's top left corner.
+#[derive(Debug, Clone)]
+pub struct LevelRegion {
+    terrain: Terrain<LevelTileLatest>,
+    objects: Vec<WlvObject>,
+}
+
+impl LevelRegion {
+    pub fn terrain(&self) -> &Terrain<LevelTileLatest> {
+        &self.terrain
+    }
+
+    pub fn objects(&self) -> &[WlvObject] {
+        &self.objects
+    }
+
+    /// Object indices are the region's.
+    pub fn dangling_references(&self) -> Vec<DanglingReference> {
+        let ids = region_ids(&self.objects);
+        dangling_references(&self.objects, 0, |id| ids.contains_key(&id))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PasteReport {
+    /// Where the pasted objects ended up in the level's objects.
+    pub objects: Range<usize>,
+    /// The region's objects that would have landed outside the level, by their index in the region.
+    pub skipped_objects: Vec<usize>,
+    /// Each ID the pasted objects had, and the one it was changed to.
+    pub new_ids: BTreeMap<i32, i32>,
+    /// Object indices are the level's.
+    pub dangling_references: Vec<DanglingReference>,
+}
+
+impl Level {
+    /// The rectangle is clipped to the level. None if nothing of it is left.
+    pub fn copy_region(&self, x: i32, y: i32, width: i32, height: i32) -> Option<LevelRegion> {
+        let (left, top) = (x.max(0), y.max(0));
+        let right = x.saturating_add(width).min(self.terrain().width());
+        let bottom = y.saturating_add(height).min(self.terrain().height());
+        if left >= right || top >= bottom {
+            return None;
+        }
+        let objects = self.objects().iter()
+            .filter(|object| {
+                let pos = object.game_object.tile_pos();
+                (left..right).contains(&pos.x) && (top..bottom).contains(&pos.y)
+            })
+            .map(|object| {
+                let mut object = object.clone();
+                object.translate(-left, -top);
+                object
+            })
+            .collect();
+        Some(LevelRegion {
+            terrain: self.terrain().resized(right - left, bottom - top, -left, -top, &LevelTileLatest::default()),
+            objects,
+        })
+    }
+
+    /// Pastes the region with its top left corner at the given tile, leaving out whatever lands outside the level.
+    /// The pasted objects' IDs are changed to ones the level doesn't use yet, and their references to each other follow.
+    pub fn paste_region(&mut self, region: &LevelRegion, x: i32, y: i32) -> PasteReport {
+        let mut report = PasteReport::default();
+        for (region_x, region_y) in region.terrain.positions() {
+            let (level_tile, water_tile) = region.terrain.cell(region_x, region_y).expect("positions should be inside the terrain");
+            if let Some((tile, water)) = self.terrain_mut().cell_mut(x + region_x, y + region_y) {
+                *tile = *level_tile;
+                *water = *water_tile;
+            }
+        }
+
+        let mut objects = vec![];
+        for (i, object) in region.objects.iter().enumerate() {
+            let mut object = object.clone();
+            object.translate(x, y);
+            let pos = object.game_object.tile_pos();
+            if self.terrain().contains(pos.x, pos.y) {
+                objects.push(object);
+            } else {
+                report.skipped_objects.push(i);
+            }
+        }
+
+        let next_id = self.objects().iter()
+            .flat_map(|object| referenced_ids(&object.game_object))
+            .max()
+            .map_or(0, |id| id + 1);
+        report.new_ids = region_ids(&objects).into_keys().zip(next_id..).collect();
+        let first = self.objects().len();
+        report.dangling_references = dangling_references(&objects, first, |id| report.new_ids.contains_key(&id));
+        for object in &mut objects {
+            let object = &mut object.game_object;
+            for reference in ObjectReference::ALL {
+                if let Some(&new_id) = reference.get(object).and_then(|id| report.new_ids.get(&id)) {
+                    reference.set(object, new_id);
+                }
+            }
+            if let Some(&new_id) = report.new_ids.get(&object.ancient_object().id()) {
+                object.ancient_object_mut().set_id(new_id);
+            }
+        }
+        self.objects_mut().extend(objects);
+        report.objects = first..self.objects().len();
+        report
+    }
+}
+
+// The IDs the objects have, each with the index of the first object that has it.
+fn region_ids(objects: &[WlvObject]) -> BTreeMap<i32, usize> {
+    let mut ids = BTreeMap::new();
+    for (i, object) in objects.iter().enumerate() {
+        let id = object.game_object.ancient_object().id();
+        if id >= 0 {
+            ids.entry(id).or_insert(i);
+        }
+    }
+    ids
+}
+
+// An object's own ID along with every ID it refers to.
+fn referenced_ids(object: &GameObject) -> impl Iterator<Item = i32> + '_ {
+    std::iter::once(object.ancient_object().id())
+        .chain(ObjectReference::ALL.into_iter().filter_map(|reference| reference.get(object)))
+}
+
+fn dangling_references<F>(objects: &[WlvObject], first_index: usize, is_inside: F) -> Vec<DanglingReference>
+where
+    F: Fn(i32) -> bool
+{
+    let mut dangling = vec![];
+    for (i, object) in objects.iter().enumerate() {
+        for reference in ObjectReference::ALL {
+            if let Some(id) = reference.get(&object.game_object).filter(|&id| !is_inside(id)) {
+                dangling.push(DanglingReference { object: first_index + i, reference, id });
+            }
+        }
+    }
+    dangling
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(x: i32, y: i32, id: i32) -> WlvObject {
+        let mut object = GameObject::new("!Button".to_owned(), "".to_owned(), ObjectLogic::COMMAND_BUTTON);
+        object.set_tile_pos(Vector2::new(x, y));
+        object.ancient_object_mut().set_id(id);
+        object.into()
+    }
+
+    #[test]
+    fn test_copy_and_paste() {
+        let mut source = Level::new(4, 4);
+        source.terrain_mut()[(1, 1)].set_logic(LevelTileLogic::LAVA);
+        let mut button = object(1, 1, 5);
+        button.game_object.set_command(Cmd::toggle(6));
+        button.game_object.set_linked(9);
+        source.add_object(button);
+        source.add_object(object(2, 2, 6));
+        source.add_object(object(3, 3, 9));
+
+        let region = source.copy_region(1, 1, 2, 2).unwrap();
+        assert_eq!(region.objects().len(), 2);
+        assert_eq!(region.objects()[1].game_object.tile_pos(), Vector2::new(1, 1));
+        assert_eq!(region.dangling_references(), [DanglingReference { object: 0, reference: ObjectReference::Linked, id: 9 }]);
+        assert!(source.copy_region(4, 0, 2, 2).is_none());
+
+        let mut destination = Level::new(3, 3);
+        destination.add_object(object(0, 0, 6));
+        let report = destination.paste_region(&region, 2, 0);
+        assert_eq!(report.objects, 1..2);
+        assert_eq!(report.skipped_objects, [1]);
+        assert_eq!(report.new_ids, BTreeMap::from([(5, 7)]));
+        assert_eq!(report.dangling_references.len(), 2);
+        assert_eq!(report.dangling_references[1], DanglingReference { object: 1, reference: ObjectReference::Command, id: 6 });
+
+        let pasted = &destination.objects()[1].game_object;
+        assert_eq!(pasted.ancient_object().id(), 7);
+        assert_eq!(pasted.tile_pos(), Vector2::new(2, 0));
+        assert_eq!(destination.terrain()[(2, 0)].logic(), LevelTileLogic::LAVA);
+
+        let report = destination.paste_region(&region, 0, 0);
+        // IDs that are only referred to count as used too.
+        assert_eq!(report.new_ids, BTreeMap::from([(5, 10), (6, 11)]));
+        let button = &destination.objects()[2].game_object;
+        assert_eq!(button.command(), Some(Cmd::toggle(11)));
+        assert_eq!(button.linked(), 9);
+    }
+}
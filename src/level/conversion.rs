@@ -0,0 +1,235 @@
+use std::{collections::BTreeMap, fmt};
+
+use crate::*;
+
+/// One way of deciding a beta tile's logic. Rules are tried in order and the first one that matches wins.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TileLogicRule {
+    /// Always matches. Flat tiles are floor, raised tiles are walls and sunken tiles are water.
+    Extrusion,
+    /// Matches tiles with the given top texture index.
+    Texture { texture: i32, logic: LevelTileLogic },
+    /// Matches tiles lower than the given height.
+    HeightBelow { height: f32, logic: LevelTileLogic },
+    /// Matches tiles higher than the given height.
+    HeightAbove { height: f32, logic: LevelTileLogic },
+}
+
+impl TileLogicRule {
+    pub fn apply(&self, tile: &LevelTileBeta) -> Option<LevelTileLogic> {
+        match *self {
+            TileLogicRule::Extrusion => Some(LevelTileLogic::from_extrusion(tile.extrusion())),
+            TileLogicRule::Texture { texture, logic } => Some(logic).filter(|_| tile.texture() == texture),
+            TileLogicRule::HeightBelow { height, logic } => Some(logic).filter(|_| tile.height() < height),
+            TileLogicRule::HeightAbove { height, logic } => Some(logic).filter(|_| tile.height() > height)
+        }
+    }
+}
+
+impl fmt::Display for TileLogicRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TileLogicRule::Extrusion => write!(f, "extrusion"),
+            TileLogicRule::Texture { texture, logic } => write!(f, "texture {} is {}", texture, logic),
+            TileLogicRule::HeightBelow { height, logic } => write!(f, "height below {} is {}", height, logic),
+            TileLogicRule::HeightAbove { height, logic } => write!(f, "height above {} is {}", height, logic)
+        }
+    }
+}
+
+/// Called on every converted object along with its index. Returns a note for the report if it changed anything.
+pub type ObjectHook = Box<dyn Fn(usize, &mut WlvObject) -> Option<String>>;
+
+/// Choices to make when converting a beta level to the latest format.
+/// The default makes the same choices as `From<WlvBeta> for Level`.
+pub struct ConversionOptions {
+    /// Only used for tiles whose logic isn't stored in a save file.
+    /// Tiles that no rule matches become floor.
+    pub tile_logic_rules: Vec<TileLogicRule>,
+    /// Maps beta tile texture indices to new ones, for both top and side textures.
+    pub tile_texture_remap: BTreeMap<i32, i32>,
+    pub level_texture: LevelTexture,
+    pub water_texture: WaterTexture,
+    pub edge_style: LevelEdgeStyle,
+    pub music: Music,
+    pub weather: Weather,
+    /// Used instead of the save file's light goals, or the default light if there are none.
+    pub light: Option<ColorRgb<i32>>,
+    pub ambient: Option<ColorRgb<i32>>,
+    pub adventure_title: String,
+    pub object_hooks: Vec<ObjectHook>,
+}
+
+impl Default for ConversionOptions {
+    fn default() -> Self {
+        Self {
+            tile_logic_rules: vec![TileLogicRule::Extrusion],
+            tile_texture_remap: BTreeMap::new(),
+            level_texture: LevelTexture::custom("wa_beta".to_owned()),
+            water_texture: WaterTexture::default(),
+            edge_style: LevelEdgeStyle::default(),
+            music: Music::NONE,
+            weather: Weather::NONE,
+            light: None,
+            ambient: None,
+            adventure_title: String::new(),
+            object_hooks: vec![],
+        }
+    }
+}
+
+impl ConversionOptions {
+    /// Returns the logic along with the rule that picked it, if any did.
+    pub fn tile_logic(&self, tile: &LevelTileBeta) -> (LevelTileLogic, Option<&TileLogicRule>) {
+        self.tile_logic_rules.iter()
+            .find_map(|rule| rule.apply(tile).map(|logic| (logic, Some(rule))))
+            .unwrap_or((LevelTileLogic::FLOOR, None))
+    }
+}
+
+/// A guess or a default that was used while converting, rather than something read from the level.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConversionDecision {
+    TileLogic { x: i32, y: i32, logic: LevelTileLogic, rule: Option<TileLogicRule> },
+    TextureRemapped { x: i32, y: i32, side: bool, from: i32, to: i32 },
+    Setting { name: &'static str, value: String },
+    Object { index: usize, note: String },
+}
+
+impl fmt::Display for ConversionDecision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConversionDecision::TileLogic { x, y, logic, rule: Some(rule) } => write!(f, "tile ({}, {}) is {} by rule \"{}\"", x, y, logic, rule),
+            ConversionDecision::TileLogic { x, y, logic, rule: None } => write!(f, "tile ({}, {}) is {} since no rule matched", x, y, logic),
+            ConversionDecision::TextureRemapped { x, y, side, from, to } => {
+                write!(f, "tile ({}, {}) {} texture {} remapped to {}", x, y, if *side { "side" } else { "top" }, from, to)
+            },
+            ConversionDecision::Setting { name, value } => write!(f, "{} set to {}", name, value),
+            ConversionDecision::Object { index, note } => write!(f, "object {}: {}", index, note)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConversionReport {
+    pub decisions: Vec<ConversionDecision>,
+}
+
+impl fmt::Display for ConversionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for decision in &self.decisions {
+            writeln!(f, "{}", decision)?;
+        }
+        Ok(())
+    }
+}
+
+/// Something the beta layout can't store, dropped while downgrading a level.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DowngradeLoss {
+    /// Tile logic other than what the tile's extrusion implies.
+    TileLogic { x: i32, y: i32, logic: LevelTileLogic },
+    Setting { name: &'static str, value: String },
+    /// An object field the beta doesn't have, named as in the text formats.
+    ObjectField { index: usize, field: String, value: String },
+}
+
+impl fmt::Display for DowngradeLoss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DowngradeLoss::TileLogic { x, y, logic } => write!(f, "tile ({}, {}) logic {} dropped", x, y, logic),
+            DowngradeLoss::Setting { name, value } => write!(f, "{} {} dropped", name, value),
+            DowngradeLoss::ObjectField { index, field, value } => write!(f, "object {}: {} {} dropped", index, field, value)
+        }
+    }
+}
+
+/// Only lists what converting back with the default options wouldn't restore.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DowngradeReport {
+    pub losses: Vec<DowngradeLoss>,
+}
+
+impl fmt::Display for DowngradeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for loss in &self.losses {
+            writeln!(f, "{}", loss)?;
+        }
+        Ok(())
+    }
+}
+
+impl DowngradeReport {
+    // Fields are compared against a fresh object's, as the ancient object doesn't keep any of them.
+    pub(crate) fn add_object_losses(&mut self, index: usize, object: &WlvObject) {
+        let fresh = GameObject::new(String::new(), String::new(), ObjectLogic::NONE);
+        let fields = object.game_object.latest_only_fields().into_iter().zip(fresh.latest_only_fields());
+        for ((field, value), (_, default)) in fields {
+            if value != default {
+                self.losses.push(DowngradeLoss::ObjectField { index, field: field.to_owned(), value });
+            }
+        }
+        for (i, adjuster) in object.adjusters.iter().enumerate().filter(|(_, adjuster)| !adjuster.is_empty()) {
+            self.losses.push(DowngradeLoss::ObjectField { index, field: format!("adjusters[{}]", i), value: format!("{:?}", adjuster) });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion_options() {
+        let mut beta = WlvBeta::new(2, 1);
+        let options = ConversionOptions {
+            tile_logic_rules: vec![TileLogicRule::HeightAbove { height: 1.0, logic: LevelTileLogic::WALL }, TileLogicRule::Texture { texture: 0, logic: LevelTileLogic::LAVA }],
+            tile_texture_remap: BTreeMap::from([(13, 20)]),
+            music: Music(3),
+            ..Default::default()
+        };
+        let (level, report) = WlvBeta::new(2, 1).convert(&options);
+        assert_eq!(level.music(), Music(3));
+        assert_eq!(report.decisions[0], ConversionDecision::TextureRemapped { x: 0, y: 0, side: true, from: 13, to: 20 });
+        assert_eq!(report.decisions[1], ConversionDecision::TileLogic { x: 0, y: 0, logic: LevelTileLogic::LAVA, rule: Some(options.tile_logic_rules[1]) });
+        assert_eq!(report.to_string().lines().nth(1), Some("tile (0, 0) is lava by rule \"texture 0 is lava\""));
+
+        beta.set_save_extras(Some(BetaSaveExtras {
+            level_tile_logics: vec![LevelTileLogic::WATER; 2],
+            light_goals: LightGoals {
+                light: LightGoal { goal: ColorRgb::splat(10), change_speed: 0 },
+                ambient: LightGoal { goal: ColorRgb::splat(20), change_speed: 0 },
+            },
+        }));
+        let (level, report) = beta.convert(&ConversionOptions::default());
+        assert_eq!(level.light(), ColorRgb::splat(10));
+        assert!(!report.decisions.iter().any(|decision| matches!(decision, ConversionDecision::TileLogic { .. })));
+    }
+
+    #[test]
+    fn test_downgrade() {
+        let mut level = Level::new(2, 1);
+        let terrain = level.terrain().clone().map_level_tiles(|i, tile| {
+            if i == 1 { LevelTileLatest::new(*tile.beta(), LevelTileLogic::LAVA) } else { tile }
+        });
+        level.set_terrain(terrain);
+        level.set_music(Music(5));
+        let mut object = GameObject::new("!Key".to_owned(), "".to_owned(), ObjectLogic::NONE);
+        object.set_movement_speed(3);
+        let mut object = WlvObject::from(object);
+        object.adjusters[2] = "x".to_owned();
+        level.add_object(object);
+
+        let (beta, report) = level.downgrade();
+        assert_eq!(beta.objects().len(), 1);
+        assert_eq!(beta.objects()[0].ancient_object().model_name(), "!Key");
+        assert_eq!(report.losses, vec![
+            DowngradeLoss::TileLogic { x: 1, y: 0, logic: LevelTileLogic::LAVA },
+            DowngradeLoss::Setting { name: "level texture", value: LevelTexture::default().name().to_owned() },
+            DowngradeLoss::Setting { name: "music", value: "5".to_owned() },
+            DowngradeLoss::ObjectField { index: 0, field: "movement_speed".to_owned(), value: "3".to_owned() },
+            DowngradeLoss::ObjectField { index: 0, field: "adjusters[2]".to_owned(), value: "\"x\"".to_owned() },
+        ]);
+        assert_eq!(Level::from(beta).terrain().width(), 2);
+    }
+}
@@ -0,0 +1,76 @@
+use std::ops::Range;
+
+use crate::*;
+use crate::random::*;
+
+/// A random variation to make to tiles. Each property is picked from its own choices,
+/// and those without any choices aren't touched.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Decoration {
+    pub textures: Vec<i32>,
+    pub rotations: Vec<i32>,
+    pub side_textures: Vec<i32>,
+    pub random: Option<Range<f32>>,
+}
+
+impl Decoration {
+    pub fn apply<R>(&self, rng: &mut R, tile: &mut LevelTileLatest)
+    where
+        R: Rng<usize> + Rng<f32>
+    {
+        let beta = tile.beta_mut();
+        if !self.textures.is_empty() { beta.set_texture(*self.textures.choose(rng)) }
+        if !self.rotations.is_empty() { beta.set_rotation(*self.rotations.choose(rng)) }
+        if !self.side_textures.is_empty() { beta.set_side_texture(*self.side_textures.choose(rng)) }
+        if let Some(random) = &self.random { beta.set_random(rng.random_range(random.start, random.end)) }
+    }
+}
+
+// The same random number generator in the same state always decorates the same tiles the same way.
+impl Terrain<LevelTileLatest> {
+    /// Decorates each tile matching the predicate with the given chance.
+    pub fn decorate_chance<R, P>(&mut self, rng: &mut R, predicate: P, chance: Chance, decoration: &Decoration)
+    where
+        R: Rng<u32> + Rng<usize> + Rng<f32>,
+        P: Fn(&LevelTileLatest) -> bool
+    {
+        self.level_tiles_mut().randomly_modify_target_chance(rng, predicate, |rng, tile| decoration.apply(rng, tile), chance);
+    }
+
+    /// Decorates as many of the tiles matching the predicate as given, or all of them if there aren't that many.
+    pub fn decorate_count<R, P>(&mut self, rng: &mut R, predicate: P, count: usize, decoration: &Decoration)
+    where
+        R: Rng<usize> + Rng<f32>,
+        P: Fn(&LevelTileLatest) -> bool
+    {
+        self.level_tiles_mut().randomly_modify_target_count(rng, predicate, |rng, tile| decoration.apply(rng, tile), count);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decorated(seed: u64) -> Terrain<LevelTileLatest> {
+        let mut terrain: Terrain<LevelTileLatest> = Terrain::new(8, 8);
+        terrain.fill_rect(0, 0, 8, 2, &TileBrush { logic: Some(LevelTileLogic::WALL), ..Default::default() });
+        let decoration = Decoration { textures: vec![1, 2, 3], rotations: vec![0, 1, 2, 3], random: Some(0.1..0.3), ..Default::default() };
+        let mut rng = Pcg32::new(seed, 0);
+        terrain.decorate_chance(&mut rng, |tile: &LevelTileLatest| tile.logic() == LevelTileLogic::FLOOR, Chance::new(1, 2), &decoration);
+        terrain.decorate_count(&mut rng, |tile: &LevelTileLatest| tile.logic() == LevelTileLogic::WALL, 5, &Decoration { side_textures: vec![20], ..Default::default() });
+        terrain
+    }
+
+    #[test]
+    fn test_decorate() {
+        let terrain = decorated(5);
+        assert_eq!(terrain, decorated(5));
+        assert_ne!(terrain, decorated(6));
+        let (walls, floors) = terrain.level_tiles().split_at(16);
+        assert!(walls.iter().all(|tile| tile.beta().texture() == 0 && tile.beta().random() == 0.0));
+        assert_eq!(walls.iter().filter(|tile| tile.beta().side_texture() == 20).count(), 5);
+        let changed: Vec<_> = floors.iter().filter(|tile| tile.beta().texture() != 0).collect();
+        assert!(!changed.is_empty() && changed.len() < floors.len());
+        assert!(changed.iter().all(|tile| (0.1..0.3).contains(&tile.beta().random()) && (0..4).contains(&tile.beta().rotation())));
+    }
+}
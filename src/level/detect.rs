@@ -0,0 +1,171 @@
+use std::{fmt, fs, path::Path};
+
+use crate::*;
+
+/// Anything bigger than this is assumed to be a misparse rather than a real level.
+pub const MAX_PLAUSIBLE_DIMENSION: i32 = 1000;
+pub const MAX_PLAUSIBLE_OBJECTS: usize = 10000;
+const MAX_PLAUSIBLE_NAME_LENGTH: usize = 260;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WlvVersion {
+    Beta,
+    BetaSave,
+    Vanilla,
+    OpenWa,
+}
+
+impl fmt::Display for WlvVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WlvVersion::Beta => write!(f, "beta"),
+            WlvVersion::BetaSave => write!(f, "beta save file"),
+            WlvVersion::Vanilla => write!(f, "vanilla WA3"),
+            WlvVersion::OpenWa => write!(f, "OpenWA")
+        }
+    }
+}
+
+/// Which layout a WLV matched, and why it's believed to be that layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WlvDetection {
+    pub version: WlvVersion,
+    pub reason: String,
+}
+
+impl fmt::Display for WlvDetection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.version, self.reason)
+    }
+}
+
+/// A WLV of any known version.
+#[derive(Debug)]
+pub enum AnyWlv {
+    Beta(WlvBeta),
+    Latest(Level),
+}
+
+impl AnyWlv {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<(Self, WlvDetection), Error> {
+        Self::from_bytes(&fs::read(path).map_err(Error::InputOutput)?)
+    }
+
+    /// Tries every known layout. A layout only matches if it uses up the input exactly and the result looks like a real level.
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, WlvDetection), Error> {
+        let latest = Level::from_bytes(bytes).map_err(|error| error.to_string()).and_then(|level| {
+            check_latest(&level).map(|summary| (level, summary))
+        });
+        let beta = WlvBeta::from_bytes(bytes).map_err(|error| error.to_string()).and_then(|beta| {
+            check_beta(&beta).map(|summary| (beta, summary))
+        });
+
+        match (latest, beta) {
+            (Ok((level, summary)), beta) => {
+                let version = if level.open_wa().is_some() { WlvVersion::OpenWa } else { WlvVersion::Vanilla };
+                let mut reason = format!("parsed exactly as {}: {}", version, summary);
+                if beta.is_ok() {
+                    reason.push_str(", though it also parses as beta");
+                }
+                Ok((AnyWlv::Latest(level), WlvDetection { version, reason }))
+            },
+            (Err(_), Ok((beta, summary))) => {
+                let version = if beta.save_extras().is_some() { WlvVersion::BetaSave } else { WlvVersion::Beta };
+                Ok((AnyWlv::Beta(beta), WlvDetection {
+                    version,
+                    reason: format!("parsed exactly as {}: {}", version, summary),
+                }))
+            },
+            (Err(latest), Err(beta)) => Err(Error::Serde(serde_blitz3d::Error::Message(
+                format!("no known WLV layout matched (latest: {}; beta: {})", latest, beta)
+            )))
+        }
+    }
+
+    pub fn version(&self) -> WlvVersion {
+        match self {
+            AnyWlv::Beta(beta) if beta.save_extras().is_some() => WlvVersion::BetaSave,
+            AnyWlv::Beta(_) => WlvVersion::Beta,
+            AnyWlv::Latest(level) if level.open_wa().is_some() => WlvVersion::OpenWa,
+            AnyWlv::Latest(_) => WlvVersion::Vanilla
+        }
+    }
+
+    pub fn into_level(self) -> Level {
+        match self {
+            AnyWlv::Beta(beta) => beta.into(),
+            AnyWlv::Latest(level) => level
+        }
+    }
+}
+
+pub fn detect_wlv_version(bytes: &[u8]) -> Result<WlvDetection, Error> {
+    AnyWlv::from_bytes(bytes).map(|(_, detection)| detection)
+}
+
+fn check_dimensions<T>(terrain: &Terrain<T>) -> Result<(), String> {
+    if terrain.width() > MAX_PLAUSIBLE_DIMENSION || terrain.height() > MAX_PLAUSIBLE_DIMENSION {
+        return Err(format!("implausible size {}x{}", terrain.width(), terrain.height()));
+    }
+    Ok(())
+}
+
+fn check_object_count(count: usize) -> Result<(), String> {
+    if count > MAX_PLAUSIBLE_OBJECTS {
+        return Err(format!("implausible object count {}", count));
+    }
+    Ok(())
+}
+
+fn check_name(what: &str, name: &str) -> Result<(), String> {
+    if name.len() > MAX_PLAUSIBLE_NAME_LENGTH || !name.chars().all(|c| c == ' ' || c.is_ascii_graphic()) {
+        return Err(format!("implausible {} {:?}", what, name));
+    }
+    Ok(())
+}
+
+fn check_latest(level: &Level) -> Result<String, String> {
+    check_dimensions(level.terrain())?;
+    check_object_count(level.objects().len())?;
+    check_name("level texture", level.level_texture().name())?;
+    check_name("water texture", level.water_texture().name())?;
+    check_name("adventure title", level.adventure_title())?;
+    Ok(format!("{}x{} terrain, {} objects, level texture {:?}",
+        level.terrain().width(), level.terrain().height(), level.objects().len(), level.level_texture().name()))
+}
+
+fn check_beta(beta: &WlvBeta) -> Result<String, String> {
+    check_dimensions(beta.terrain())?;
+    check_object_count(beta.objects().len())?;
+    Ok(format!("{}x{} terrain, {} objects", beta.terrain().width(), beta.terrain().height(), beta.objects().len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_wlv_version() {
+        let mut level = Level::new(4, 3);
+        let vanilla = level.to_bytes(WlvFormat::Vanilla).unwrap();
+        assert_eq!(detect_wlv_version(&vanilla).unwrap().version, WlvVersion::Vanilla);
+
+        level.set_open_wa(Some(OpenWaExtension::new(1)));
+        let open_wa = level.to_bytes(WlvFormat::OpenWa).unwrap();
+        assert_eq!(detect_wlv_version(&open_wa).unwrap().version, WlvVersion::OpenWa);
+
+        let mut beta = WlvBeta::new(4, 3);
+        assert_eq!(detect_wlv_version(&beta.to_bytes().unwrap()).unwrap().version, WlvVersion::Beta);
+        beta.set_save_extras(Some(BetaSaveExtras {
+            level_tile_logics: vec![LevelTileLogic::FLOOR; 12],
+            light_goals: LightGoals {
+                light: LightGoal { goal: ColorRgb::DEFAULT_LIGHT, change_speed: 0 },
+                ambient: LightGoal { goal: ColorRgb::DEFAULT_AMBIENT, change_speed: 0 },
+            },
+        }));
+        assert_eq!(detect_wlv_version(&beta.to_bytes().unwrap()).unwrap().version, WlvVersion::BetaSave);
+
+        assert!(detect_wlv_version(&vanilla[..vanilla.len() - 1]).is_err());
+        assert!(detect_wlv_version(&[0xff; 64]).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct LevelEdgeStyle(i32);
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelEdgeStyle(pub i32);
 
 impl LevelEdgeStyle {
     pub const DEFAULT: Self = Self(1);
@@ -0,0 +1,285 @@
+use std::collections::VecDeque;
+
+use crate::*;
+use crate::random::*;
+
+/// How the walkable floor of a generated level is laid out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    /// Corridors one tile wide, with exactly one way between any two floor tiles.
+    Maze,
+    /// Caverns grown from random walls by a cellular automaton.
+    Cave {
+        /// How many tiles start out as walls, out of 100.
+        wall_percent: u32,
+        smoothing_steps: u32,
+    },
+    /// One landmass in the middle, surrounded by water.
+    Island {
+        /// How far the coast strays from a circle, from 0 to 1.
+        roughness: f32,
+    },
+}
+
+/// Choices to make when generating a level.
+/// The same options with the same seed always generate the same level.
+#[derive(Debug, Clone)]
+pub struct GenerationOptions {
+    pub layout: Layout,
+    pub seed: u64,
+    pub floor: TileBrush,
+    pub wall: TileBrush,
+    pub water: TileBrush,
+    pub edge_style: LevelEdgeStyle,
+    /// Put on the floor tile farthest from where the player starts, if there is one.
+    pub goal: Option<GameObject>,
+}
+
+impl GenerationOptions {
+    /// Uses square walls for mazes and round, jagged ones otherwise.
+    pub fn new(layout: Layout, seed: u64) -> Self {
+        let (rounding, edge_random, random) = match layout {
+            Layout::Maze => (Rounding::SQUARE, EdgeRandom::SMOOTH, 0.0),
+            Layout::Cave { .. } | Layout::Island { .. } => (Rounding::ROUND, EdgeRandom::JAGGED, 0.2)
+        };
+        Self {
+            layout,
+            seed,
+            floor: TileBrush {
+                logic: Some(LevelTileLogic::FLOOR),
+                height: Some(0.0),
+                extrusion: Some(0.0),
+                ..Default::default()
+            },
+            wall: TileBrush {
+                logic: Some(LevelTileLogic::WALL),
+                extrusion: Some(1.0),
+                random: Some(random),
+                rounding: Some(rounding),
+                edge_random: Some(edge_random),
+                ..Default::default()
+            },
+            water: TileBrush {
+                logic: Some(LevelTileLogic::WATER),
+                extrusion: Some(-0.5),
+                rounding: Some(rounding),
+                edge_random: Some(edge_random),
+                water_height: Some(-0.2),
+                ..Default::default()
+            },
+            edge_style: LevelEdgeStyle::default(),
+            goal: None,
+        }
+    }
+}
+
+impl Level {
+    /// Generates a level whose floor is all connected, with the player as the first object and the goal, if any, as the second.
+    pub fn generate(width: i32, height: i32, options: &GenerationOptions) -> Self {
+        let mut rng = Pcg32::new(options.seed, 0);
+        let mut terrain = Terrain::new(width, height);
+        let filler = match options.layout {
+            Layout::Maze => {
+                carve_maze(&mut terrain, &mut rng);
+                LevelTileLogic::WALL
+            },
+            Layout::Cave { wall_percent, smoothing_steps } => {
+                let chance = Chance::new(wall_percent.min(100), 100);
+                for (x, y) in terrain.positions().collect::<Vec<_>>() {
+                    let on_border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+                    if on_border || chance.happens(&mut rng) {
+                        terrain[(x, y)].set_logic(LevelTileLogic::WALL);
+                    }
+                }
+                smooth(&mut terrain, LevelTileLogic::WALL, smoothing_steps);
+                LevelTileLogic::WALL
+            },
+            Layout::Island { roughness } => {
+                let (half_width, half_height) = (width as f32 / 2.0, height as f32 / 2.0);
+                for (x, y) in terrain.positions().collect::<Vec<_>>() {
+                    let dx = (x as f32 + 0.5 - half_width) / half_width;
+                    let dy = (y as f32 + 0.5 - half_height) / half_height;
+                    let jitter = if roughness > 0.0 { rng.random_range(-roughness, roughness) } else { 0.0 };
+                    if 1.0 - (dx * dx + dy * dy).sqrt() + jitter < 0.4 {
+                        terrain[(x, y)].set_logic(LevelTileLogic::WATER);
+                    }
+                }
+                smooth(&mut terrain, LevelTileLogic::WATER, 2);
+                LevelTileLogic::WATER
+            }
+        };
+        keep_largest_floor_region(&mut terrain, filler);
+        let floors: Vec<_> = terrain.positions().filter(|&(x, y)| is_floor(&terrain, x, y)).collect();
+        let start = if floors.is_empty() {
+            let center = (width / 2, height / 2);
+            terrain[center].set_logic(LevelTileLogic::FLOOR);
+            center
+        } else {
+            *floors.choose(&mut rng)
+        };
+
+        for (x, y) in terrain.positions().collect::<Vec<_>>() {
+            let brush = match terrain[(x, y)].logic() {
+                LevelTileLogic::WALL => &options.wall,
+                LevelTileLogic::WATER => &options.water,
+                _ => &options.floor
+            };
+            terrain.paint(x, y, brush);
+        }
+
+        let mut level = Level::new(width, height);
+        level.set_edge_style(options.edge_style);
+        level.add_object(placed(GameObject::new("!Player".to_owned(), "".to_owned(), ObjectLogic::PLAYER), start));
+        if let Some(goal) = &options.goal {
+            let distances = floor_distances(&terrain, start);
+            let farthest = distances.iter().enumerate()
+                .filter_map(|(i, distance)| distance.map(|distance| (distance, i)))
+                .max_by_key(|&(distance, i)| (distance, std::cmp::Reverse(i)))
+                .filter(|&(distance, _)| distance > 0);
+            if let Some((_, i)) = farthest {
+                level.add_object(placed(goal.clone(), terrain.coordinates(i)));
+            }
+        }
+        level.set_terrain(terrain);
+        level
+    }
+}
+
+fn placed(mut object: GameObject, (x, y): (i32, i32)) -> WlvObject {
+    object.set_tile_pos(Vector2::new(x, y));
+    object.set_world_pos(Vector3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0));
+    object.into()
+}
+
+fn is_floor(terrain: &Terrain<LevelTileLatest>, x: i32, y: i32) -> bool {
+    terrain.get(x, y).is_some_and(|tile| tile.logic() == LevelTileLogic::FLOOR)
+}
+
+// The recursive backtracker, with rooms on odd coordinates and walls on even ones.
+fn carve_maze(terrain: &mut Terrain<LevelTileLatest>, rng: &mut Pcg32) {
+    for tile in terrain.level_tiles_mut() {
+        tile.set_logic(LevelTileLogic::WALL);
+    }
+    let (rooms_x, rooms_y) = ((terrain.width() - 1) / 2, (terrain.height() - 1) / 2);
+    if rooms_x == 0 || rooms_y == 0 {
+        return;
+    }
+    let start = (2 * rng.random_bounded(rooms_x) + 1, 2 * rng.random_bounded(rooms_y) + 1);
+    terrain[start].set_logic(LevelTileLogic::FLOOR);
+    let mut stack = vec![start];
+    while let Some(&(x, y)) = stack.last() {
+        let unvisited: Vec<_> = [(0, -2), (2, 0), (0, 2), (-2, 0)].into_iter()
+            .map(|(dx, dy)| (x + dx, y + dy))
+            .filter(|&(x, y)| x < 2 * rooms_x && y < 2 * rooms_y && terrain.get(x, y).is_some_and(|tile| tile.logic() == LevelTileLogic::WALL))
+            .collect();
+        if unvisited.is_empty() {
+            stack.pop();
+            continue;
+        }
+        let next = *unvisited.choose(rng);
+        terrain[((x + next.0) / 2, (y + next.1) / 2)].set_logic(LevelTileLogic::FLOOR);
+        terrain[next].set_logic(LevelTileLogic::FLOOR);
+        stack.push(next);
+    }
+}
+
+// Turns tiles surrounded mostly by the solid logic into it, and those surrounded mostly by floor into floor.
+// The outside of the terrain counts as solid.
+fn smooth(terrain: &mut Terrain<LevelTileLatest>, solid: LevelTileLogic, steps: u32) {
+    for _ in 0..steps {
+        let logics: Vec<_> = terrain.positions()
+            .map(|(x, y)| {
+                let inside: Vec<_> = terrain.neighbors(x, y, Neighborhood::All).collect();
+                let solid_count = 8 - inside.len() + inside.iter().filter(|&&position| terrain[position].logic() == solid).count();
+                match solid_count {
+                    0..=3 => LevelTileLogic::FLOOR,
+                    4 => terrain[(x, y)].logic(),
+                    _ => solid
+                }
+            })
+            .collect();
+        for (tile, logic) in terrain.level_tiles_mut().iter_mut().zip(logics) {
+            tile.set_logic(logic);
+        }
+    }
+}
+
+// How many steps it takes to walk from the start to each tile, walking only on floor.
+fn floor_distances(terrain: &Terrain<LevelTileLatest>, start: (i32, i32)) -> Vec<Option<usize>> {
+    let mut distances = vec![None; terrain.level_tiles().len()];
+    let mut queue = VecDeque::new();
+    if let Some(i) = terrain.index_of(start.0, start.1).filter(|_| is_floor(terrain, start.0, start.1)) {
+        distances[i] = Some(0);
+        queue.push_back((start, 0));
+    }
+    while let Some(((x, y), distance)) = queue.pop_front() {
+        for (x, y) in terrain.neighbors(x, y, Neighborhood::Orthogonal) {
+            let i = terrain.index_of(x, y).expect("neighbors should be inside the terrain");
+            if distances[i].is_none() && is_floor(terrain, x, y) {
+                distances[i] = Some(distance + 1);
+                queue.push_back(((x, y), distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+// Fills in every floor tile that can't be walked to from the biggest stretch of floor.
+fn keep_largest_floor_region(terrain: &mut Terrain<LevelTileLatest>, filler: LevelTileLogic) {
+    let mut region = vec![None; terrain.level_tiles().len()];
+    let mut largest: Option<(usize, usize)> = None;
+    for (x, y) in terrain.positions() {
+        let i = terrain.index_of(x, y).expect("positions should be inside the terrain");
+        if region[i].is_some() || !is_floor(terrain, x, y) {
+            continue;
+        }
+        let mut size = 0;
+        for (j, distance) in floor_distances(terrain, (x, y)).into_iter().enumerate() {
+            if distance.is_some() {
+                region[j] = Some(i);
+                size += 1;
+            }
+        }
+        if largest.is_none_or(|(_, largest_size)| size > largest_size) {
+            largest = Some((i, size));
+        }
+    }
+    for (tile, region) in terrain.level_tiles_mut().iter_mut().zip(region) {
+        if region.is_some() && region != largest.map(|(i, _)| i) {
+            tile.set_logic(filler);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_levels_are_playable() {
+        let goal = GameObject::new("!Star".to_owned(), "".to_owned(), ObjectLogic::NONE);
+        let layouts = [Layout::Maze, Layout::Cave { wall_percent: 45, smoothing_steps: 4 }, Layout::Island { roughness: 0.3 }];
+        for layout in layouts {
+            let mut options = GenerationOptions::new(layout, 7);
+            options.goal = Some(goal.clone());
+            let level = Level::generate(21, 15, &options);
+            assert_eq!(level.to_bytes(WlvFormat::Vanilla).unwrap(), Level::generate(21, 15, &options).to_bytes(WlvFormat::Vanilla).unwrap());
+
+            let player = &level.objects()[0].game_object;
+            assert_eq!(player.logic(), ObjectLogic::PLAYER);
+            let start = (player.tile_pos().x, player.tile_pos().y);
+            let distances = floor_distances(level.terrain(), start);
+            for (i, tile) in level.terrain().level_tiles().iter().enumerate() {
+                assert_eq!(tile.logic() == LevelTileLogic::FLOOR, distances[i].is_some(), "{:?} at {:?}", layout, level.terrain().coordinates(i));
+            }
+            let goal_pos = level.objects()[1].game_object.tile_pos();
+            assert!(distances[level.terrain().index_of(goal_pos.x, goal_pos.y).unwrap()].unwrap() > 0);
+        }
+
+        let maze = Level::generate(5, 5, &GenerationOptions::new(Layout::Maze, 1));
+        assert_eq!(maze.terrain().level_tiles().iter().filter(|tile| tile.logic() == LevelTileLogic::FLOOR).count(), 7);
+        assert_eq!(maze.terrain()[(0, 0)].beta().extrusion(), 1.0);
+        let other_seed = Level::generate(21, 15, &GenerationOptions::new(Layout::Maze, 8));
+        assert_ne!(other_seed.to_bytes(WlvFormat::Vanilla).unwrap(), Level::generate(21, 15, &GenerationOptions::new(Layout::Maze, 7)).to_bytes(WlvFormat::Vanilla).unwrap());
+    }
+}
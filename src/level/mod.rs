@@ -1,11 +1,29 @@
+pub mod brush;
+pub use brush::*;
+pub mod clipboard;
+pub use clipboard::*;
+pub mod conversion;
+pub use conversion::*;
+pub mod decorate;
+pub use decorate::*;
+pub mod detect;
+pub use detect::*;
 pub mod edge_style;
 pub use edge_style::*;
+pub mod generate;
+pub use generate::*;
 pub mod music;
 pub use music::*;
+pub mod open_wa;
+pub use open_wa::*;
+pub mod sculpt;
+pub use sculpt::*;
 pub mod texture;
 pub use texture::*;
 pub mod terrain;
 pub use terrain::*;
+pub mod transform;
+pub use transform::*;
 pub mod weather;
 pub use weather::*;
 pub mod wlv;
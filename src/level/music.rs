@@ -1,7 +1,7 @@
 use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Music(i32);
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Music(pub i32);
 
 impl Music {
     pub const NONE: Self = Self(0);
@@ -0,0 +1,94 @@
+use std::fmt;
+
+use crate::*;
+use serde::{Serialize, Deserialize};
+
+/// Which variant of the latest WLV format to write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WlvFormat {
+    /// What the original Wonderland Adventures 3 reads and writes. Drops any OpenWA extension.
+    Vanilla,
+    /// Vanilla followed by the OpenWA extension block.
+    OpenWa,
+}
+
+impl fmt::Display for WlvFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WlvFormat::Vanilla => write!(f, "vanilla"),
+            WlvFormat::OpenWa => write!(f, "OpenWA")
+        }
+    }
+}
+
+/// The block OpenWA writes after the adventure title.
+/// It starts with a negative version number so it can't be mistaken for vanilla data.
+/// Fields from versions newer than this crate knows about are kept as raw bytes and written back unchanged.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct OpenWaExtension {
+    version: i32,
+    pub widescreen_range_level: i32,
+    unknown: Vec<u8>,
+}
+
+impl OpenWaExtension {
+    /// The first version, which only has WidescreenRangeLevel.
+    pub const VERSION_1: i32 = -2;
+
+    pub fn new(widescreen_range_level: i32) -> Self {
+        Self {
+            version: Self::VERSION_1,
+            widescreen_range_level,
+            unknown: vec![],
+        }
+    }
+
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    /// Bytes written by newer versions of OpenWA that aren't understood yet.
+    pub fn unknown(&self) -> &[u8] {
+        &self.unknown
+    }
+
+    /// Parses whatever follows the adventure title. Returns None if nothing does.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Option<Self>, Error> {
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        let version = read_i32(bytes, 0).ok_or_else(|| malformed("truncated OpenWA version"))?;
+        if version > Self::VERSION_1 {
+            return Err(malformed(&format!("unexpected data after the level, starting with {}", version)));
+        }
+        let widescreen_range_level = read_i32(bytes, 4).ok_or_else(|| malformed("truncated OpenWA WidescreenRangeLevel"))?;
+        Ok(Some(Self {
+            version,
+            widescreen_range_level,
+            unknown: bytes[8..].to_vec(),
+        }))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(8 + self.unknown.len());
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.extend_from_slice(&self.widescreen_range_level.to_le_bytes());
+        bytes.extend_from_slice(&self.unknown);
+        bytes
+    }
+}
+
+impl Default for OpenWaExtension {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+fn read_i32(bytes: &[u8], offset: usize) -> Option<i32> {
+    let bytes = bytes.get(offset..offset + 4)?;
+    Some(i32::from_le_bytes(bytes.try_into().expect("slice should be 4 bytes long")))
+}
+
+fn malformed(message: &str) -> Error {
+    Error::Serde(serde_blitz3d::Error::Message(message.to_owned()))
+}
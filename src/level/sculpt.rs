@@ -0,0 +1,334 @@
+use std::f32::consts::PI;
+
+use crate::*;
+use crate::random::*;
+
+/// The tiles a filter is allowed to change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileMask {
+    width: i32,
+    height: i32,
+    cells: Vec<bool>,
+}
+
+impl TileMask {
+    pub fn from_fn<F>(width: i32, height: i32, mut f: F) -> Self
+    where
+        F: FnMut(i32, i32) -> bool
+    {
+        Self {
+            width,
+            height,
+            cells: (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| f(x, y)).collect(),
+        }
+    }
+
+    /// Covers all of a terrain of the given size.
+    pub fn all(width: i32, height: i32) -> Self {
+        Self::from_fn(width, height, |_, _| true)
+    }
+
+    /// Covers only the given rectangle of a terrain of the given size.
+    pub fn rect(width: i32, height: i32, x: i32, y: i32, rect_width: i32, rect_height: i32) -> Self {
+        Self::from_fn(width, height, |tile_x, tile_y| (x..x + rect_width).contains(&tile_x) && (y..y + rect_height).contains(&tile_y))
+    }
+
+    /// Covers the tiles matching the predicate.
+    pub fn matching<P>(terrain: &Terrain<LevelTileLatest>, mut predicate: P) -> Self
+    where
+        P: FnMut(&LevelTileLatest, &WaterTile) -> bool
+    {
+        Self::from_fn(terrain.width(), terrain.height(), |x, y| {
+            let (tile, water) = terrain.cell(x, y).expect("the mask should be the terrain's size");
+            predicate(tile, water)
+        })
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// False outside the mask.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.width && y < self.height && self.cells[(y * self.width + x) as usize]
+    }
+
+    /// Does nothing outside the mask.
+    pub fn set(&mut self, x: i32, y: i32, value: bool) {
+        if x >= 0 && y >= 0 && x < self.width && y < self.height {
+            self.cells[(y * self.width + x) as usize] = value;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SmoothingKernel {
+    /// The plain average of the tiles within the radius, in a square.
+    Box { radius: i32 },
+    /// An average that weighs nearer tiles more, out to three standard deviations.
+    Gaussian { sigma: f32 },
+}
+
+impl SmoothingKernel {
+    fn radius(self) -> i32 {
+        match self {
+            SmoothingKernel::Box { radius } => radius.max(0),
+            SmoothingKernel::Gaussian { sigma } => (3.0 * sigma.max(0.0)).ceil() as i32
+        }
+    }
+
+    fn weight(self, dx: i32, dy: i32) -> f32 {
+        match self {
+            SmoothingKernel::Gaussian { sigma } if sigma > 0.0 => (-((dx * dx + dy * dy) as f32) / (2.0 * sigma * sigma)).exp(),
+            _ => 1.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseKind {
+    /// Random values at the corners of each cell, blended across it.
+    Value,
+    /// Random slopes at the corners of each cell, which gives smoother, less blocky hills.
+    Perlin,
+}
+
+/// Smooth 2D noise that's the same wherever it's sampled for the same random numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Noise {
+    kind: NoiseKind,
+    cell_size: f32,
+    permutation: Vec<usize>,
+    // Values for value noise, or gradient angles in half turns for Perlin noise. From -1 to 1 either way.
+    lattice: Vec<f32>,
+}
+
+impl Noise {
+    const LATTICE_SIZE: usize = 256;
+
+    /// The cell size is how many tiles apart the random values are. Returns None unless it's positive.
+    pub fn new<R>(kind: NoiseKind, cell_size: f32, rng: &mut R) -> Option<Self>
+    where
+        R: Rng<usize> + Rng<f32>
+    {
+        if cell_size.is_nan() || cell_size <= 0.0 {
+            return None;
+        }
+        let mut permutation: Vec<_> = (0..Self::LATTICE_SIZE).collect();
+        permutation.shuffle_all(rng);
+        let lattice = (0..Self::LATTICE_SIZE).map(|_| rng.random_range(-1.0, 1.0)).collect();
+        Some(Self {
+            kind,
+            cell_size,
+            permutation,
+            lattice,
+        })
+    }
+
+    pub fn kind(&self) -> NoiseKind {
+        self.kind
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Takes a position in tiles and gives roughly -1 to 1.
+    pub fn sample(&self, x: f32, y: f32) -> f32 {
+        let (x, y) = (x / self.cell_size, y / self.cell_size);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let corner = |dx: i32, dy: i32| {
+            let lattice = self.lattice[self.hash(x0 + dx, y0 + dy)];
+            match self.kind {
+                NoiseKind::Value => lattice,
+                NoiseKind::Perlin => {
+                    let angle = lattice * PI;
+                    // Scaled so that the result reaches about as far as value noise does.
+                    (angle.cos() * (tx - dx as f32) + angle.sin() * (ty - dy as f32)) * std::f32::consts::SQRT_2
+                }
+            }
+        };
+        let (fx, fy) = (fade(tx), fade(ty));
+        let top = lerp(corner(0, 0), corner(1, 0), fx);
+        let bottom = lerp(corner(0, 1), corner(1, 1), fx);
+        lerp(top, bottom, fy)
+    }
+
+    fn hash(&self, x: i32, y: i32) -> usize {
+        let size = Self::LATTICE_SIZE as i32;
+        self.permutation[(self.permutation[x.rem_euclid(size) as usize] + y.rem_euclid(size) as usize) % Self::LATTICE_SIZE]
+    }
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+// Every filter only changes the heights of tiles inside the mask, and never those of walls or water.
+// Each returns how many tiles it changed.
+impl Terrain<LevelTileLatest> {
+    /// Averages each height with those around it. Walls and water don't count towards the averages.
+    pub fn smooth_heights(&mut self, mask: &TileMask, kernel: SmoothingKernel) -> usize {
+        let radius = kernel.radius();
+        self.update_heights(mask, |terrain, x, y| {
+            let (mut total, mut total_weight) = (0.0, 0.0);
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    if let Some(tile) = terrain.get(x + dx, y + dy).filter(|tile| is_sculptable(tile)) {
+                        let weight = kernel.weight(dx, dy);
+                        total += tile.beta().height() * weight;
+                        total_weight += weight;
+                    }
+                }
+            }
+            total / total_weight
+        })
+    }
+
+    /// Raises and lowers each height by up to the amplitude, following the noise at the tile's center.
+    pub fn displace_heights(&mut self, mask: &TileMask, noise: &Noise, amplitude: f32) -> usize {
+        self.update_heights(mask, |terrain, x, y| {
+            terrain[(x, y)].beta().height() + amplitude * noise.sample(x as f32 + 0.5, y as f32 + 0.5)
+        })
+    }
+
+    /// Rounds each height down to a multiple of the step, turning slopes into flat ledges.
+    pub fn terrace(&mut self, mask: &TileMask, step: f32) -> usize {
+        if step <= 0.0 {
+            return 0;
+        }
+        self.update_heights(mask, |terrain, x, y| (terrain[(x, y)].beta().height() / step).floor() * step)
+    }
+
+    /// Wears down height differences between neighbors that are steeper than the maximum slope,
+    /// moving height from the higher tile to the lower one like loose earth sliding downhill.
+    /// Only tiles that can both be changed trade height, so the total height stays the same.
+    pub fn limit_slopes(&mut self, mask: &TileMask, max_slope: f32, iterations: u32) -> usize {
+        let max_slope = max_slope.max(0.0);
+        let original: Vec<_> = self.level_tiles().iter().map(|tile| tile.beta().height()).collect();
+        for _ in 0..iterations {
+            let mut changes = vec![0.0; original.len()];
+            for (x, y) in self.positions() {
+                if !self.is_sculptable_at(mask, x, y) {
+                    continue;
+                }
+                let i = self.index_of(x, y).expect("positions should be inside the terrain");
+                // Each pair is looked at once, from its left or top tile.
+                for (nx, ny) in [(x + 1, y), (x, y + 1)] {
+                    if !self.is_sculptable_at(mask, nx, ny) {
+                        continue;
+                    }
+                    let j = self.index_of(nx, ny).expect("sculptable tiles should be inside the terrain");
+                    let difference = self.level_tiles()[i].beta().height() - self.level_tiles()[j].beta().height();
+                    let excess = difference.abs() - max_slope;
+                    if excess > 0.0 {
+                        // A quarter at most, since a tile can slide towards all four neighbors at once.
+                        let moved = excess / 4.0 * difference.signum();
+                        changes[i] -= moved;
+                        changes[j] += moved;
+                    }
+                }
+            }
+            if changes.iter().all(|&change| change == 0.0) {
+                break;
+            }
+            for (tile, change) in self.level_tiles_mut().iter_mut().zip(changes) {
+                let height = tile.beta().height() + change;
+                tile.beta_mut().set_height(height);
+            }
+        }
+        self.level_tiles().iter().zip(original).filter(|(tile, height)| tile.beta().height() != *height).count()
+    }
+
+    fn is_sculptable_at(&self, mask: &TileMask, x: i32, y: i32) -> bool {
+        mask.contains(x, y) && self.get(x, y).is_some_and(is_sculptable)
+    }
+
+    // Works out every new height from the old ones before changing any.
+    fn update_heights<F>(&mut self, mask: &TileMask, mut f: F) -> usize
+    where
+        F: FnMut(&Self, i32, i32) -> f32
+    {
+        let heights: Vec<_> = self.positions()
+            .filter(|&(x, y)| self.is_sculptable_at(mask, x, y))
+            .map(|(x, y)| ((x, y), f(self, x, y)))
+            .collect();
+        let mut changed = 0;
+        for (position, height) in heights {
+            let beta = self[position].beta_mut();
+            if beta.height() != height {
+                beta.set_height(height);
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
+
+fn is_sculptable(tile: &LevelTileLatest) -> bool {
+    tile.logic() != LevelTileLogic::WALL && tile.logic() != LevelTileLogic::WATER
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heights(terrain: &Terrain<LevelTileLatest>) -> Vec<f32> {
+        terrain.level_tiles().iter().map(|tile| tile.beta().height()).collect()
+    }
+
+    #[test]
+    fn test_filters() {
+        let mut terrain: Terrain<LevelTileLatest> = Terrain::new(5, 5);
+        terrain[(2, 2)].beta_mut().set_height(9.0);
+        terrain[(0, 0)].set_logic(LevelTileLogic::WALL);
+        terrain[(0, 0)].beta_mut().set_height(100.0);
+        let mask = TileMask::all(5, 5);
+        assert_eq!(terrain.clone().smooth_heights(&mask, SmoothingKernel::Box { radius: 1 }), 9);
+        terrain.smooth_heights(&mask, SmoothingKernel::Box { radius: 1 });
+        assert_eq!(terrain[(2, 2)].beta().height(), 1.0);
+        assert_eq!(terrain[(1, 1)].beta().height(), 9.0 / 8.0);
+        assert_eq!(terrain[(0, 0)].beta().height(), 100.0);
+
+        terrain.terrace(&mask, 1.0);
+        assert_eq!(terrain[(1, 1)].beta().height(), 1.0);
+        assert_eq!(terrain.terrace(&TileMask::rect(5, 5, 0, 0, 2, 2), 0.5), 0);
+
+        let mut terrain: Terrain<LevelTileLatest> = Terrain::new(5, 1);
+        terrain[(2, 0)].beta_mut().set_height(4.0);
+        let total: f32 = heights(&terrain).iter().sum();
+        assert_eq!(terrain.limit_slopes(&TileMask::rect(5, 1, 1, 0, 3, 1), 0.5, 100), 3);
+        let limited = heights(&terrain);
+        assert!((limited.iter().sum::<f32>() - total).abs() < 1e-3, "{:?}", limited);
+        assert!(limited.windows(2).skip(1).take(2).all(|pair| (pair[0] - pair[1]).abs() <= 0.5 + 1e-3), "{:?}", limited);
+
+        let mut rng = Pcg32::new(3, 0);
+        let noise = Noise::new(NoiseKind::Perlin, 4.0, &mut rng).unwrap();
+        assert_eq!(Some(&noise), Noise::new(NoiseKind::Perlin, 4.0, &mut Pcg32::new(3, 0)).as_ref());
+        assert!(Noise::new(NoiseKind::Value, 0.0, &mut rng).is_none());
+        assert!(Noise::new(NoiseKind::Value, -1.0, &mut rng).is_none());
+        assert!(Noise::new(NoiseKind::Value, f32::NAN, &mut rng).is_none());
+        let mut terrain: Terrain<LevelTileLatest> = Terrain::new(8, 8);
+        terrain[(3, 3)].set_logic(LevelTileLogic::WATER);
+        assert!(terrain.displace_heights(&mask_without_corner(8), &noise, 2.0) > 0);
+        assert!(heights(&terrain).iter().all(|height| height.abs() <= 2.0 * 1.5));
+        assert_eq!(terrain[(3, 3)].beta().height(), 0.0);
+        assert_eq!(terrain[(7, 7)].beta().height(), 0.0);
+    }
+
+    fn mask_without_corner(size: i32) -> TileMask {
+        let mut mask = TileMask::all(size, size);
+        mask.set(size - 1, size - 1, false);
+        mask
+    }
+}
@@ -1,12 +1,11 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::{Index, IndexMut}};
 
 use crate::*;
 use serde::{Serialize, Deserialize, Deserializer, de::{self, Visitor, SeqAccess}};
 
 /// The type parameter T determines the data format of the level tiles,
 /// which varies between game versions.
-#[derive(Serialize, Debug)]
-
+#[derive(Serialize, Debug, Clone, PartialEq)]
 pub struct Terrain<T> {
     width: i32,
     height: i32,
@@ -14,13 +13,192 @@ pub struct Terrain<T> {
     water_tiles: Vec<WaterTile>,
 }
 
+impl<T> Terrain<T>
+where
+    T: Default + Clone
+{
+    /// Creates flat terrain filled with default tiles.
+    pub fn new(width: i32, height: i32) -> Self {
+        assert!(width > 0 && height > 0, "terrain should be at least 1x1");
+        let total = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            level_tiles: vec![T::default(); total],
+            water_tiles: vec![WaterTile::default(); total],
+        }
+    }
+}
+
+impl<T> Terrain<T> {
+    /// Creates terrain from the tiles for each pair of coordinates, row by row.
+    /// Panics if the size is smaller than 1x1.
+    pub fn from_fn<F>(width: i32, height: i32, mut f: F) -> Self
+    where
+        F: FnMut(i32, i32) -> (T, WaterTile)
+    {
+        assert!(width > 0 && height > 0, "terrain should be at least 1x1");
+        let (level_tiles, water_tiles) = (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).map(|(x, y)| f(x, y)).unzip();
+        Self {
+            width,
+            height,
+            level_tiles,
+            water_tiles,
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.width && y < self.height
+    }
+
+    /// Where the tile at these coordinates is in `level_tiles` and `water_tiles`, which go row by row.
+    pub fn index_of(&self, x: i32, y: i32) -> Option<usize> {
+        self.contains(x, y).then(|| y as usize * self.width as usize + x as usize)
+    }
+
+    pub fn coordinates(&self, index: usize) -> (i32, i32) {
+        (index as i32 % self.width, index as i32 / self.width)
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> Option<&T> {
+        self.index_of(x, y).map(|i| &self.level_tiles[i])
+    }
+
+    pub fn get_mut(&mut self, x: i32, y: i32) -> Option<&mut T> {
+        self.index_of(x, y).map(|i| &mut self.level_tiles[i])
+    }
+
+    pub fn water(&self, x: i32, y: i32) -> Option<&WaterTile> {
+        self.index_of(x, y).map(|i| &self.water_tiles[i])
+    }
+
+    pub fn water_mut(&mut self, x: i32, y: i32) -> Option<&mut WaterTile> {
+        self.index_of(x, y).map(|i| &mut self.water_tiles[i])
+    }
+
+    /// The level tile and water tile at the same coordinates.
+    pub fn cell(&self, x: i32, y: i32) -> Option<(&T, &WaterTile)> {
+        self.index_of(x, y).map(|i| (&self.level_tiles[i], &self.water_tiles[i]))
+    }
+
+    pub fn cell_mut(&mut self, x: i32, y: i32) -> Option<(&mut T, &mut WaterTile)> {
+        self.index_of(x, y).map(|i| (&mut self.level_tiles[i], &mut self.water_tiles[i]))
+    }
+
+    pub fn level_tiles(&self) -> &[T] {
+        &self.level_tiles
+    }
+
+    pub fn level_tiles_mut(&mut self) -> &mut [T] {
+        &mut self.level_tiles
+    }
+
+    pub fn water_tiles(&self) -> &[WaterTile] {
+        &self.water_tiles
+    }
+
+    pub fn water_tiles_mut(&mut self) -> &mut [WaterTile] {
+        &mut self.water_tiles
+    }
+
+    /// Every cell's coordinates, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (i32, i32)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn row(&self, y: i32) -> Option<&[T]> {
+        self.index_of(0, y).map(|start| &self.level_tiles[start..start + self.width as usize])
+    }
+
+    pub fn row_mut(&mut self, y: i32) -> Option<&mut [T]> {
+        let width = self.width as usize;
+        self.index_of(0, y).map(move |start| &mut self.level_tiles[start..start + width])
+    }
+
+    /// From top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.level_tiles.chunks(self.width as usize)
+    }
+
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [T]> {
+        self.level_tiles.chunks_mut(self.width as usize)
+    }
+
+    pub fn column(&self, x: i32) -> Option<impl Iterator<Item = &T>> {
+        self.index_of(x, 0).map(|start| self.level_tiles[start..].iter().step_by(self.width as usize))
+    }
+
+    /// From left to right, each from top to bottom.
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| self.level_tiles[x as usize..].iter().step_by(self.width as usize))
+    }
+
+    /// The coordinates of the cells around this one that are inside the terrain.
+    pub fn neighbors(&self, x: i32, y: i32, neighborhood: Neighborhood) -> impl Iterator<Item = (i32, i32)> + '_ {
+        neighborhood.offsets().iter()
+            .map(move |(dx, dy)| (x + dx, y + dy))
+            .filter(move |&(x, y)| self.contains(x, y))
+    }
+
+    /// Converts every level tile, passing along its index.
+    pub fn map_level_tiles<U, F>(self, mut f: F) -> Terrain<U>
+    where
+        F: FnMut(usize, T) -> U
+    {
+        Terrain {
+            width: self.width,
+            height: self.height,
+            level_tiles: self.level_tiles.into_iter().enumerate().map(|(i, tile)| f(i, tile)).collect(),
+            water_tiles: self.water_tiles,
+        }
+    }
+}
+
 impl From<Terrain<LevelTileBeta>> for Terrain<LevelTileLatest> {
     fn from(value: Terrain<LevelTileBeta>) -> Self {
-        Self {
-            width: value.width,
-            height: value.height,
-            level_tiles: value.level_tiles.into_iter().map(|tile| tile.into()).collect(),
-            water_tiles: value.water_tiles,
+        value.map_level_tiles(|_, tile| tile.into())
+    }
+}
+
+impl<T> Index<(i32, i32)> for Terrain<T> {
+    type Output = T;
+
+    /// Panics if the coordinates are outside the terrain.
+    fn index(&self, (x, y): (i32, i32)) -> &T {
+        self.get(x, y).unwrap_or_else(|| panic!("({}, {}) is outside the {}x{} terrain", x, y, self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<(i32, i32)> for Terrain<T> {
+    fn index_mut(&mut self, (x, y): (i32, i32)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(x, y).unwrap_or_else(|| panic!("({}, {}) is outside the {}x{} terrain", x, y, width, height))
+    }
+}
+
+/// Which surrounding cells count as neighbors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    /// The four cells sharing an edge.
+    Orthogonal,
+    /// All eight surrounding cells.
+    All,
+}
+
+impl Neighborhood {
+    fn offsets(self) -> &'static [(i32, i32)] {
+        match self {
+            Neighborhood::Orthogonal => &[(0, -1), (-1, 0), (1, 0), (0, 1)],
+            Neighborhood::All => &[(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)]
         }
     }
 }
@@ -33,6 +211,23 @@ where
     where
         D: Deserializer<'de>
     {
+        if deserializer.is_human_readable() {
+            let fields = TerrainFields::deserialize(deserializer)?;
+            if fields.width <= 0 || fields.height <= 0 {
+                return Err(de::Error::custom(format!("invalid terrain size {}x{}", fields.width, fields.height)));
+            }
+            let total = fields.width as usize * fields.height as usize;
+            if fields.level_tiles.len() != total || fields.water_tiles.len() != total {
+                return Err(de::Error::custom(format!("a {}x{} terrain needs {} level tiles and water tiles, not {} and {}",
+                    fields.width, fields.height, total, fields.level_tiles.len(), fields.water_tiles.len())));
+            }
+            return Ok(Terrain {
+                width: fields.width,
+                height: fields.height,
+                level_tiles: fields.level_tiles,
+                water_tiles: fields.water_tiles,
+            });
+        }
         deserializer.deserialize_struct("LevelContent", &[
             "width",
             "height",
@@ -42,6 +237,16 @@ where
     }
 }
 
+// How human-readable formats lay out terrain, since the tile counts are written out rather than implied by the size.
+#[derive(Deserialize)]
+#[serde(rename = "Terrain")]
+struct TerrainFields<T> {
+    width: i32,
+    height: i32,
+    level_tiles: Vec<T>,
+    water_tiles: Vec<WaterTile>,
+}
+
 pub struct TerrainVisitor<T> {
     phantom: PhantomData<T>
 }
@@ -70,13 +275,16 @@ where
     {
         let width: i32 = seq.next_element()?.ok_or_else(|| de::Error::custom("unexpected end of sequence"))?;
         let height: i32 = seq.next_element()?.ok_or_else(|| de::Error::custom("unexpected end of sequence"))?;
+        if width <= 0 || height <= 0 {
+            return Err(de::Error::custom(format!("invalid terrain size {}x{}", width, height)));
+        }
         let total = width as usize * height as usize;
-        let mut level_tiles = Vec::with_capacity(total);
+        let mut level_tiles = Vec::with_capacity(total.min(serde_blitz3d::MAX_PREALLOCATION));
         for _ in 0..total {
             let tile = seq.next_element()?.ok_or_else(|| de::Error::custom("unexpected end of sequence"))?;
             level_tiles.push(tile);
         }
-        let mut water_tiles = Vec::with_capacity(total);
+        let mut water_tiles = Vec::with_capacity(total.min(serde_blitz3d::MAX_PREALLOCATION));
         for _ in 0..total {
             let tile = seq.next_element()?.ok_or_else(|| de::Error::custom("unexpected end of sequence"))?;
             water_tiles.push(tile);
@@ -88,4 +296,34 @@ where
             water_tiles,
         })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_access() {
+        let mut terrain: Terrain<LevelTileLatest> = Terrain::new(3, 2);
+        terrain[(2, 1)].set_logic(LevelTileLogic::LAVA);
+        terrain.get_mut(0, 1).unwrap().beta_mut().set_height(2.0);
+        terrain.water_mut(1, 0).unwrap().height = 1.5;
+        assert!(terrain.get(3, 0).is_none() && terrain.get(0, -1).is_none());
+        assert_eq!(terrain.index_of(2, 1), Some(5));
+        assert_eq!(terrain.coordinates(5), (2, 1));
+        assert_eq!(terrain.cell(1, 0).unwrap().1.height, 1.5);
+
+        assert_eq!(terrain.row(1).unwrap()[2].logic(), LevelTileLogic::LAVA);
+        assert_eq!(terrain.rows().count(), 2);
+        let heights: Vec<f32> = terrain.column(0).unwrap().map(|tile| tile.beta().height()).collect();
+        assert_eq!(heights, [0.0, 2.0]);
+        assert_eq!(terrain.columns().map(|column| column.count()).collect::<Vec<_>>(), [2, 2, 2]);
+        assert_eq!(terrain.positions().nth(4), Some((1, 1)));
+
+        assert_eq!(terrain.neighbors(0, 0, Neighborhood::Orthogonal).collect::<Vec<_>>(), [(1, 0), (0, 1)]);
+        assert_eq!(terrain.neighbors(1, 0, Neighborhood::All).count(), 5);
+
+        let beta: Terrain<LevelTileBeta> = Terrain::new(1, 1);
+        assert_eq!(beta[(0, 0)].side_texture(), 13);
+    }
+}
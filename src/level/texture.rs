@@ -1,6 +1,6 @@
 use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct LevelTexture(String);
 
 impl LevelTexture {
@@ -14,6 +14,10 @@ impl LevelTexture {
     pub fn custom(name: String) -> Self {
         Self(name)
     }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
 }
 
 impl Default for LevelTexture {
@@ -22,7 +26,7 @@ impl Default for LevelTexture {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct WaterTexture(String);
 
 impl WaterTexture {
@@ -38,6 +42,10 @@ impl WaterTexture {
     pub fn custom(name: String) -> Self {
         Self(name)
     }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
 }
 
 impl Default for WaterTexture {
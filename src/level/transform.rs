@@ -0,0 +1,303 @@
+use crate::*;
+
+// The map is seen from above with north up: x grows to the east and y grows to the south.
+// Objects stand on tiles at world x and y, with world z as their height above the floor.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Clockwise90,
+    Rotate180,
+    Clockwise270,
+}
+
+impl Rotation {
+    fn quarter_turns(self) -> usize {
+        match self {
+            Rotation::Clockwise90 => 1,
+            Rotation::Rotate180 => 2,
+            Rotation::Clockwise270 => 3
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mirror {
+    /// East and west swap.
+    LeftRight,
+    /// North and south swap.
+    TopBottom,
+}
+
+// How everything on the grid moves when the whole grid does.
+#[derive(Debug, Clone, Copy)]
+enum GridTransform {
+    Translate { dx: i32, dy: i32 },
+    /// A quarter turn clockwise of a grid this tall.
+    QuarterTurn { height: i32 },
+    Mirror { mirror: Mirror, width: i32, height: i32 },
+}
+
+impl GridTransform {
+    fn tile(self, x: i32, y: i32) -> (i32, i32) {
+        match self {
+            GridTransform::Translate { dx, dy } => (x + dx, y + dy),
+            GridTransform::QuarterTurn { height } => (height - 1 - y, x),
+            GridTransform::Mirror { mirror: Mirror::LeftRight, width, .. } => (width - 1 - x, y),
+            GridTransform::Mirror { mirror: Mirror::TopBottom, height, .. } => (x, height - 1 - y)
+        }
+    }
+
+    fn point(self, x: f32, y: f32) -> (f32, f32) {
+        match self {
+            GridTransform::Translate { dx, dy } => (x + dx as f32, y + dy as f32),
+            GridTransform::QuarterTurn { height } => (height as f32 - y, x),
+            GridTransform::Mirror { mirror: Mirror::LeftRight, width, .. } => (width as f32 - x, y),
+            GridTransform::Mirror { mirror: Mirror::TopBottom, height, .. } => (x, height as f32 - y)
+        }
+    }
+
+    fn direction(self, x: f32, y: f32) -> (f32, f32) {
+        match self {
+            GridTransform::Translate { .. } => (x, y),
+            GridTransform::QuarterTurn { .. } => (-y, x),
+            GridTransform::Mirror { mirror: Mirror::LeftRight, .. } => (-x, y),
+            GridTransform::Mirror { mirror: Mirror::TopBottom, .. } => (x, -y)
+        }
+    }
+
+    // Mirrored objects keep their pitch, since the model itself can't be mirrored.
+    fn angles(self, angles: EulerAngles) -> EulerAngles {
+        let (yaw, roll) = match self {
+            GridTransform::Translate { .. } => return angles,
+            GridTransform::QuarterTurn { .. } => (angles.yaw - 90.0, angles.roll),
+            GridTransform::Mirror { mirror: Mirror::LeftRight, .. } => (-angles.yaw, -angles.roll),
+            GridTransform::Mirror { mirror: Mirror::TopBottom, .. } => (180.0 - angles.yaw, -angles.roll)
+        };
+        EulerAngles::new(angles.pitch, yaw.rem_euclid(360.0), roll)
+    }
+
+    // Texture rotations count quarter turns clockwise in the lowest two bits, and 4 means flipped left to right first.
+    fn texture_rotation(self, rotation: i32) -> i32 {
+        let (turns, flipped) = (rotation & 3, rotation & 4);
+        let (turns, flipped) = match self {
+            GridTransform::Translate { .. } => return rotation,
+            GridTransform::QuarterTurn { .. } => (turns + 1, flipped),
+            GridTransform::Mirror { mirror: Mirror::LeftRight, .. } => (-turns, flipped ^ 4),
+            GridTransform::Mirror { mirror: Mirror::TopBottom, .. } => (2 - turns, flipped ^ 4)
+        };
+        (rotation & !7) | flipped | (turns & 3)
+    }
+
+    fn movement_type(self, movement_type: MovementType) -> MovementType {
+        match self {
+            GridTransform::Translate { .. } => movement_type,
+            GridTransform::QuarterTurn { .. } => movement_type.rotated_clockwise(),
+            GridTransform::Mirror { mirror, .. } => movement_type.mirrored(mirror)
+        }
+    }
+
+    fn level_tile<T: LevelTile + Clone>(self, tile: &T) -> T {
+        let mut tile = tile.clone();
+        let rotation = self.texture_rotation(tile.beta().rotation());
+        tile.beta_mut().set_rotation(rotation);
+        tile
+    }
+
+    fn water_tile(self, tile: &WaterTile) -> WaterTile {
+        WaterTile {
+            rotation: self.texture_rotation(tile.rotation),
+            ..*tile
+        }
+    }
+
+    fn object(self, object: &mut WlvObject) {
+        let object = &mut object.game_object;
+        let tile = |pos: Vector2<i32>| {
+            let (x, y) = self.tile(pos.x, pos.y);
+            Vector2::new(x, y)
+        };
+        object.set_tile_pos(tile(object.tile_pos()));
+        object.set_tile_pos2(tile(object.tile_pos2()));
+        object.set_move_goal(tile(object.move_goal()));
+
+        let ancient = object.ancient_object_mut();
+        let point = |pos: Vector3<f32>| {
+            let (x, y) = self.point(pos.x, pos.y);
+            Vector3::new(x, y, pos.z)
+        };
+        ancient.set_world_pos(point(ancient.world_pos()));
+        if ancient.old_world_pos() != Vector3::splat(-999.0) {
+            ancient.set_old_world_pos(point(ancient.old_world_pos()));
+        }
+        ancient.set_goal(point(ancient.goal()));
+        let delta = ancient.delta();
+        let (x, y) = self.direction(delta.x, delta.y);
+        ancient.set_delta(Vector3::new(x, y, delta.z));
+        let push_delta = ancient.push_delta();
+        let (x, y) = self.direction(push_delta.x, push_delta.y);
+        ancient.set_push_delta(Vector2::new(x, y));
+        ancient.set_rotation(self.angles(ancient.rotation()));
+        ancient.set_rotation2(self.angles(ancient.rotation2()));
+        ancient.set_movement_type(self.movement_type(ancient.movement_type()));
+    }
+}
+
+impl WlvObject {
+    /// Moves the object by whole tiles.
+    pub fn translate(&mut self, dx: i32, dy: i32) {
+        GridTransform::Translate { dx, dy }.object(self);
+    }
+}
+
+impl<T> Terrain<T>
+where
+    T: LevelTile + Clone
+{
+    /// Moves the terrain by the offset within a grid of the new size. Cells it doesn't cover get the padding.
+    /// Panics if the new size is smaller than 1x1.
+    pub fn resized(&self, width: i32, height: i32, offset_x: i32, offset_y: i32, padding: &T) -> Self {
+        Terrain::from_fn(width, height, |x, y| match self.cell(x - offset_x, y - offset_y) {
+            Some((level_tile, water_tile)) => (level_tile.clone(), *water_tile),
+            None => (padding.clone(), WaterTile::default())
+        })
+    }
+
+    /// Tile texture rotations are turned along with the terrain. Side textures are left alone.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        let mut terrain = self.clone();
+        for _ in 0..rotation.quarter_turns() {
+            let transform = GridTransform::QuarterTurn { height: terrain.height() };
+            terrain = Terrain::from_fn(terrain.height(), terrain.width(), |x, y| {
+                let (level_tile, water_tile) = terrain.cell(y, terrain.height() - 1 - x).expect("the source cell should be inside the terrain");
+                (transform.level_tile(level_tile), transform.water_tile(water_tile))
+            });
+        }
+        terrain
+    }
+
+    pub fn mirrored(&self, mirror: Mirror) -> Self {
+        let transform = GridTransform::Mirror { mirror, width: self.width(), height: self.height() };
+        Terrain::from_fn(self.width(), self.height(), |x, y| {
+            // Mirroring is its own inverse.
+            let (x, y) = transform.tile(x, y);
+            let (level_tile, water_tile) = self.cell(x, y).expect("the source cell should be inside the terrain");
+            (transform.level_tile(level_tile), transform.water_tile(water_tile))
+        })
+    }
+}
+
+impl Level {
+    /// Moves the level by the offset within a grid of the new size. Cells it doesn't cover get the padding.
+    /// Returns the objects that ended up outside, which are removed, or None if the new size is smaller than 1x1.
+    pub fn resize(&mut self, width: i32, height: i32, offset_x: i32, offset_y: i32, padding: LevelTileLatest) -> Option<Vec<WlvObject>> {
+        if width < 1 || height < 1 {
+            return None;
+        }
+        let terrain = self.terrain().resized(width, height, offset_x, offset_y, &padding);
+        self.set_terrain(terrain);
+        self.transform_objects(GridTransform::Translate { dx: offset_x, dy: offset_y });
+        let (inside, outside) = std::mem::take(self.objects_mut()).into_iter().partition(|object| {
+            let pos = object.game_object.tile_pos();
+            self.terrain().contains(pos.x, pos.y)
+        });
+        *self.objects_mut() = inside;
+        Some(outside)
+    }
+
+    /// Keeps only the given rectangle. Returns the objects outside it, which are removed, or None if the rectangle is empty.
+    pub fn crop(&mut self, x: i32, y: i32, width: i32, height: i32) -> Option<Vec<WlvObject>> {
+        self.resize(width, height, -x, -y, LevelTileLatest::default())
+    }
+
+    /// Shifts everything without changing the size. Returns the objects shifted off the edge, which are removed.
+    pub fn translate(&mut self, dx: i32, dy: i32, padding: LevelTileLatest) -> Vec<WlvObject> {
+        self.resize(self.terrain().width(), self.terrain().height(), dx, dy, padding).expect("the level should never be empty")
+    }
+
+    /// Turns objects and their directional movement types along with the terrain.
+    pub fn rotate(&mut self, rotation: Rotation) {
+        for _ in 0..rotation.quarter_turns() {
+            self.transform_objects(GridTransform::QuarterTurn { height: self.terrain().height() });
+            let terrain = self.terrain().rotated(Rotation::Clockwise90);
+            self.set_terrain(terrain);
+        }
+    }
+
+    pub fn mirror(&mut self, mirror: Mirror) {
+        self.transform_objects(GridTransform::Mirror { mirror, width: self.terrain().width(), height: self.terrain().height() });
+        let terrain = self.terrain().mirrored(mirror);
+        self.set_terrain(terrain);
+    }
+
+    fn transform_objects(&mut self, transform: GridTransform) {
+        for object in self.objects_mut() {
+            transform.object(object);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level_with_object(x: i32, y: i32) -> Level {
+        let mut level = Level::new(3, 2);
+        let mut object = GameObject::new("!Tentacle".to_owned(), "".to_owned(), ObjectLogic::NONE);
+        object.set_tile_pos(Vector2::new(x, y));
+        object.set_world_pos(Vector3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0));
+        object.set_movement_type(MovementType::NORTH_LEFT);
+        level.add_object(object.into());
+        level
+    }
+
+    #[test]
+    fn test_rotate_and_mirror() {
+        let mut level = level_with_object(2, 0);
+        level.terrain_mut()[(2, 0)].beta_mut().set_rotation(4);
+        level.rotate(Rotation::Clockwise90);
+        let object = &level.objects()[0].game_object;
+        assert_eq!((level.terrain().width(), level.terrain().height()), (2, 3));
+        assert_eq!(object.tile_pos(), Vector2::new(1, 2));
+        assert_eq!(object.ancient_object().world_pos(), Vector3::new(1.5, 2.5, 0.0));
+        assert_eq!(object.ancient_object().rotation().yaw, 270.0);
+        assert_eq!(object.ancient_object().movement_type(), MovementType::EAST_LEFT);
+        assert_eq!(level.terrain()[(1, 2)].beta().rotation(), 5);
+
+        level.rotate(Rotation::Clockwise270);
+        level.mirror(Mirror::LeftRight);
+        let object = &level.objects()[0].game_object;
+        assert_eq!(object.tile_pos(), Vector2::new(0, 0));
+        assert_eq!(object.ancient_object().rotation().yaw, 0.0);
+        assert_eq!(object.ancient_object().movement_type(), MovementType::NORTH_RIGHT);
+        assert_eq!(level.terrain()[(0, 0)].beta().rotation(), 0);
+
+        let mut level = level_with_object(0, 1);
+        level.mirror(Mirror::TopBottom);
+        assert_eq!(level.objects()[0].game_object.ancient_object().movement_type(), MovementType::SOUTH_RIGHT);
+        assert_eq!(level.objects()[0].game_object.ancient_object().rotation().yaw, 180.0);
+    }
+
+    #[test]
+    fn test_resize_and_crop() {
+        let mut level = level_with_object(2, 1);
+        level.terrain_mut()[(2, 1)].set_logic(LevelTileLogic::LAVA);
+        let removed = level.resize(5, 4, 1, 2, LevelTileLatest::new(LevelTileBeta::default(), LevelTileLogic::WALL)).unwrap();
+        assert!(removed.is_empty());
+        assert_eq!(level.terrain()[(0, 0)].logic(), LevelTileLogic::WALL);
+        assert_eq!(level.terrain()[(3, 3)].logic(), LevelTileLogic::LAVA);
+        assert_eq!(level.objects()[0].game_object.tile_pos(), Vector2::new(3, 3));
+
+        assert!(level.crop(0, 0, 0, 2).is_none());
+        assert!(level.resize(3, -1, 0, 0, LevelTileLatest::default()).is_none());
+        assert_eq!(level.objects().len(), 1);
+
+        let removed = level.crop(0, 0, 2, 2).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert!(level.objects().is_empty());
+
+        let mut level = level_with_object(0, 0);
+        assert!(level.translate(1, 0, LevelTileLatest::default()).is_empty());
+        assert_eq!(level.objects()[0].game_object.ancient_object().world_pos().x, 1.5);
+        assert_eq!(level.translate(-2, 0, LevelTileLatest::default()).len(), 1);
+    }
+}
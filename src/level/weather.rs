@@ -1,7 +1,7 @@
 use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Weather(i32);
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Weather(pub i32);
 
 impl Weather {
     pub const NONE: Self = Self(0);
@@ -1,116 +1,553 @@
-use std::{fs::File, path::Path};
+use std::{fs, path::Path};
 
 use crate::*;
 use serde::{Serialize, Deserialize};
 
+/// A level in the latest WLV format, as used by Wonderland Adventures 3.
 #[derive(Serialize, Deserialize, Debug)]
-pub(crate) struct WlvLatest {
+pub struct Level {
     terrain: Terrain<LevelTileLatest>,
     water_flow: i32,
     water_transparent: i32,
     water_glow: i32,
     level_texture_name: LevelTexture,
     water_texture_name: WaterTexture,
-    // Number of objects is written here.
-    objects: Vec<WlvObject>,
+    objects: VecLen<WlvObject>,
     edge_style: LevelEdgeStyle,
     light: ColorRgb<i32>,
     ambient: ColorRgb<i32>,
     music: Music,
     weather: Weather,
     adventure_title: String,
-    // OpenWA writes -2 here.
-    // OpenWA writes WidescreenRangeLevel here.
+    // Read and written separately, since it's only there in levels saved by OpenWA.
+    #[serde(default, with = "serde_blitz3d::human_readable_only")]
+    open_wa: Option<OpenWaExtension>,
 }
 
+pub type WlvLatest = Level;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct WlvBeta {
     terrain: Terrain<LevelTileBeta>,
     water_flow: i32,
     water_transparent: i32,
     water_glow: i32,
-    // Number of objects is written here.
-    // TODO: Other serialization should use VecLen.
     objects: VecLen<BetaObject>,
+    // Read and written separately, since it's only there in the beta's save files.
+    #[serde(default, with = "serde_blitz3d::human_readable_only")]
+    save_extras: Option<BetaSaveExtras>,
+}
 
-    // TODO: The following are only written in the beta's save files:
-    //level_tile_logics: Vec<LevelTileLogic>,
-    //light_goals: LightGoals,
+/// What the beta's save files store after the objects.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BetaSaveExtras {
+    /// One per level tile, in the same order. There's no count, since it's always the number of tiles.
+    pub level_tile_logics: Vec<LevelTileLogic>,
+    pub light_goals: LightGoals,
 }
 
-impl WlvLatest {
-    pub fn to_file(&self, filename: &str) -> Result<(), Error> {
-        let file = File::create(format!("{}.wlv", filename)).map_err(Error::InputOutput)?;
-        serde_blitz3d::to_writer(file, self).map_err(Error::Serde)
+impl Level {
+    /// Creates an empty level of flat floor tiles with the game's default settings.
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            terrain: Terrain::new(width, height),
+            water_flow: 1,
+            water_transparent: 0,
+            water_glow: 0,
+            level_texture_name: LevelTexture::default(),
+            water_texture_name: WaterTexture::default(),
+            objects: VecLen::default(),
+            edge_style: LevelEdgeStyle::default(),
+            light: ColorRgb::DEFAULT_LIGHT,
+            ambient: ColorRgb::DEFAULT_AMBIENT,
+            music: Music::NONE,
+            weather: Weather::NONE,
+            adventure_title: String::new(),
+            open_wa: None,
+        }
+    }
+
+    /// Writes the OpenWA extension only if the level has one.
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let format = if self.open_wa.is_some() { WlvFormat::OpenWa } else { WlvFormat::Vanilla };
+        self.to_file_as(path, format)
+    }
+
+    pub fn to_file_as<P: AsRef<Path>>(&self, path: P, format: WlvFormat) -> Result<(), Error> {
+        fs::write(path, self.to_bytes(format)?).map_err(Error::InputOutput)
     }
 
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        let file = File::open(path).map_err(Error::InputOutput)?;
-        serde_blitz3d::from_reader(file).map_err(Error::Serde)
+        Self::from_bytes(&fs::read(path).map_err(Error::InputOutput)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = bytes;
+        let mut level: Self = serde_blitz3d::from_reader(&mut reader).map_err(Error::Serde)?;
+        level.open_wa = OpenWaExtension::from_bytes(reader)?;
+        Ok(level)
+    }
+
+    /// An OpenWA level without an extension gets a default one.
+    pub fn to_bytes(&self, format: WlvFormat) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+        serde_blitz3d::to_writer(&mut bytes, self).map_err(Error::Serde)?;
+        if format == WlvFormat::OpenWa {
+            bytes.extend(self.open_wa.clone().unwrap_or_default().to_bytes());
+        }
+        Ok(bytes)
+    }
+
+    pub fn terrain(&self) -> &Terrain<LevelTileLatest> {
+        &self.terrain
+    }
+
+    pub fn terrain_mut(&mut self) -> &mut Terrain<LevelTileLatest> {
+        &mut self.terrain
+    }
+
+    pub fn set_terrain(&mut self, terrain: Terrain<LevelTileLatest>) {
+        self.terrain = terrain;
+    }
+
+    pub fn water_flow(&self) -> i32 {
+        self.water_flow
+    }
+
+    pub fn set_water_flow(&mut self, water_flow: i32) {
+        self.water_flow = water_flow;
+    }
+
+    pub fn water_transparent(&self) -> bool {
+        self.water_transparent != 0
+    }
+
+    pub fn set_water_transparent(&mut self, water_transparent: bool) {
+        self.water_transparent = i32::from(water_transparent);
+    }
+
+    pub fn water_glow(&self) -> bool {
+        self.water_glow != 0
+    }
+
+    pub fn set_water_glow(&mut self, water_glow: bool) {
+        self.water_glow = i32::from(water_glow);
+    }
+
+    pub fn level_texture(&self) -> &LevelTexture {
+        &self.level_texture_name
+    }
+
+    pub fn set_level_texture(&mut self, texture: LevelTexture) {
+        self.level_texture_name = texture;
+    }
+
+    pub fn water_texture(&self) -> &WaterTexture {
+        &self.water_texture_name
+    }
+
+    pub fn set_water_texture(&mut self, texture: WaterTexture) {
+        self.water_texture_name = texture;
+    }
+
+    pub fn objects(&self) -> &[WlvObject] {
+        &self.objects.0
+    }
+
+    pub fn objects_mut(&mut self) -> &mut Vec<WlvObject> {
+        &mut self.objects.0
+    }
+
+    /// Returns the index of the new object.
+    pub fn add_object(&mut self, object: WlvObject) -> usize {
+        self.objects.0.push(object);
+        self.objects.0.len() - 1
+    }
+
+    pub fn remove_object(&mut self, index: usize) -> WlvObject {
+        self.objects.0.remove(index)
+    }
+
+    pub fn edge_style(&self) -> LevelEdgeStyle {
+        self.edge_style
+    }
+
+    pub fn set_edge_style(&mut self, edge_style: LevelEdgeStyle) {
+        self.edge_style = edge_style;
+    }
+
+    pub fn light(&self) -> ColorRgb<i32> {
+        self.light
+    }
+
+    pub fn set_light(&mut self, light: ColorRgb<i32>) {
+        self.light = light;
+    }
+
+    pub fn ambient(&self) -> ColorRgb<i32> {
+        self.ambient
+    }
+
+    pub fn set_ambient(&mut self, ambient: ColorRgb<i32>) {
+        self.ambient = ambient;
+    }
+
+    pub fn music(&self) -> Music {
+        self.music
+    }
+
+    pub fn set_music(&mut self, music: Music) {
+        self.music = music;
+    }
+
+    pub fn weather(&self) -> Weather {
+        self.weather
+    }
+
+    pub fn set_weather(&mut self, weather: Weather) {
+        self.weather = weather;
+    }
+
+    pub fn adventure_title(&self) -> &str {
+        &self.adventure_title
+    }
+
+    pub fn set_adventure_title(&mut self, adventure_title: String) {
+        self.adventure_title = adventure_title;
+    }
+
+    pub fn open_wa(&self) -> Option<&OpenWaExtension> {
+        self.open_wa.as_ref()
+    }
+
+    pub fn open_wa_mut(&mut self) -> Option<&mut OpenWaExtension> {
+        self.open_wa.as_mut()
+    }
+
+    pub fn set_open_wa(&mut self, open_wa: Option<OpenWaExtension>) {
+        self.open_wa = open_wa;
     }
 }
 
 impl WlvBeta {
-    pub fn to_file(&self, filename: &str) -> Result<(), Error> {
-        let file = File::create(format!("{}.wlv", filename)).map_err(Error::InputOutput)?;
-        serde_blitz3d::to_writer(file, self).map_err(Error::Serde)
+    /// Creates an empty beta level of flat floor tiles.
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            terrain: Terrain::new(width, height),
+            water_flow: 1,
+            water_transparent: 0,
+            water_glow: 0,
+            objects: VecLen::default(),
+            save_extras: None,
+        }
+    }
+
+    /// Writes the save file extras only if the level has them.
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        fs::write(path, self.to_bytes()?).map_err(Error::InputOutput)
     }
 
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
-        let file = File::open(path).map_err(Error::InputOutput)?;
-        serde_blitz3d::from_reader(file).map_err(Error::Serde)
+        Self::from_bytes(&fs::read(path).map_err(Error::InputOutput)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = bytes;
+        let mut beta: Self = serde_blitz3d::from_reader(&mut reader).map_err(Error::Serde)?;
+        if !reader.is_empty() {
+            let tile_count = beta.terrain.width() as usize * beta.terrain.height() as usize;
+            beta.save_extras = Some(BetaSaveExtras::from_bytes(reader, tile_count)?);
+        }
+        Ok(beta)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+        serde_blitz3d::to_writer(&mut bytes, self).map_err(Error::Serde)?;
+        if let Some(save_extras) = &self.save_extras {
+            serde_blitz3d::to_writer(&mut bytes, save_extras).map_err(Error::Serde)?;
+        }
+        Ok(bytes)
+    }
+
+    pub fn terrain(&self) -> &Terrain<LevelTileBeta> {
+        &self.terrain
+    }
+
+    pub fn objects(&self) -> &[BetaObject] {
+        &self.objects.0
+    }
+
+    pub fn save_extras(&self) -> Option<&BetaSaveExtras> {
+        self.save_extras.as_ref()
+    }
+
+    pub fn set_save_extras(&mut self, save_extras: Option<BetaSaveExtras>) {
+        if let Some(save_extras) = &save_extras {
+            assert_eq!(save_extras.level_tile_logics.len(), self.terrain.width() as usize * self.terrain.height() as usize,
+                "there should be one level tile logic per level tile");
+        }
+        self.save_extras = save_extras;
     }
 
-    pub fn modernize_to_file(self, filename: &str) -> Result<(), Error> {
-        let modern: WlvLatest = self.into();
-        modern.to_file(filename)
+    pub fn modernize_to_file<P: AsRef<Path>>(self, path: P) -> Result<(), Error> {
+        let modern: Level = self.into();
+        modern.to_file(path)
+    }
+}
+
+impl BetaSaveExtras {
+    fn from_bytes(bytes: &[u8], tile_count: usize) -> Result<Self, Error> {
+        let mut reader = bytes;
+        let mut deserializer = serde_blitz3d::Deserializer::from_reader(&mut reader);
+        let level_tile_logics = (0..tile_count)
+            .map(|_| LevelTileLogic::deserialize(&mut deserializer))
+            .collect::<Result<_, _>>()
+            .map_err(Error::Serde)?;
+        let light_goals = LightGoals::deserialize(&mut deserializer).map_err(Error::Serde)?;
+        if !reader.is_empty() {
+            return Err(Error::Serde(serde_blitz3d::Error::Message(format!("{} bytes left over after the beta save file extras", reader.len()))));
+        }
+        Ok(Self {
+            level_tile_logics,
+            light_goals,
+        })
     }
 }
 
-impl From<WlvBeta> for WlvLatest {
+impl From<WlvBeta> for Level {
     fn from(other: WlvBeta) -> Self {
-        Self {
-            terrain: other.terrain.into(),
-            water_flow: other.water_flow,
-            water_transparent: other.water_transparent,
-            water_glow: other.water_glow,
-            objects: other.objects.0.into_iter().map(|object| object.into()).collect(),
-            level_texture_name: LevelTexture::custom("wa_beta".to_owned()),
-            water_texture_name: WaterTexture::default(),
-            edge_style: Default::default(),
-            light: ColorRgb::DEFAULT_LIGHT,
-            ambient: ColorRgb::DEFAULT_AMBIENT,
-            music: Music::NONE,
-            weather: Weather::NONE,
-            adventure_title: String::new(),
+        other.convert(&ConversionOptions::default()).0
+    }
+}
+
+impl WlvBeta {
+    /// Tile logic and lighting come from the save file extras if there are any.
+    /// Everything else the beta doesn't store comes from the options, and every such choice is listed in the report.
+    pub fn convert(self, options: &ConversionOptions) -> (Level, ConversionReport) {
+        let mut report = ConversionReport::default();
+        let width = self.terrain.width();
+        let save_extras = self.save_extras;
+        let terrain = self.terrain.map_level_tiles(|i, mut tile| {
+            let (x, y) = (i as i32 % width, i as i32 / width);
+            for side in [false, true] {
+                let from = if side { tile.side_texture() } else { tile.texture() };
+                if let Some(&to) = options.tile_texture_remap.get(&from) {
+                    if side { tile.set_side_texture(to) } else { tile.set_texture(to) }
+                    report.decisions.push(ConversionDecision::TextureRemapped { x, y, side, from, to });
+                }
+            }
+            let logic = match &save_extras {
+                Some(save_extras) => save_extras.level_tile_logics[i],
+                None => {
+                    let (logic, rule) = options.tile_logic(&tile);
+                    report.decisions.push(ConversionDecision::TileLogic { x, y, logic, rule: rule.copied() });
+                    logic
+                }
+            };
+            LevelTileLatest::new(tile, logic)
+        });
+
+        let mut setting = |name, value: String| report.decisions.push(ConversionDecision::Setting { name, value });
+        let format_color = |color: ColorRgb<i32>| format!("({}, {}, {})", color.red, color.green, color.blue);
+        setting("level texture", options.level_texture.name().to_owned());
+        setting("water texture", options.water_texture.name().to_owned());
+        setting("edge style", options.edge_style.0.to_string());
+        setting("music", options.music.0.to_string());
+        setting("weather", options.weather.0.to_string());
+        let light = match (options.light, &save_extras) {
+            (Some(light), _) => {
+                setting("light", format_color(light));
+                light
+            },
+            (None, Some(save_extras)) => save_extras.light_goals.light.goal,
+            (None, None) => {
+                setting("light", format_color(ColorRgb::DEFAULT_LIGHT));
+                ColorRgb::DEFAULT_LIGHT
+            }
+        };
+        let ambient = match (options.ambient, &save_extras) {
+            (Some(ambient), _) => {
+                setting("ambient", format_color(ambient));
+                ambient
+            },
+            (None, Some(save_extras)) => save_extras.light_goals.ambient.goal,
+            (None, None) => {
+                setting("ambient", format_color(ColorRgb::DEFAULT_AMBIENT));
+                ColorRgb::DEFAULT_AMBIENT
+            }
+        };
+
+        let mut objects: Vec<WlvObject> = self.objects.0.into_iter().map(|object| object.into()).collect();
+        for (index, object) in objects.iter_mut().enumerate() {
+            for hook in &options.object_hooks {
+                if let Some(note) = hook(index, object) {
+                    report.decisions.push(ConversionDecision::Object { index, note });
+                }
+            }
+        }
+
+        let level = Level {
+            terrain,
+            water_flow: self.water_flow,
+            water_transparent: self.water_transparent,
+            water_glow: self.water_glow,
+            objects: VecLen(objects),
+            level_texture_name: options.level_texture.clone(),
+            water_texture_name: options.water_texture.clone(),
+            edge_style: options.edge_style,
+            light,
+            ambient,
+            music: options.music,
+            weather: options.weather,
+            adventure_title: options.adventure_title.clone(),
+            open_wa: None,
+        };
+        (level, report)
+    }
+}
+
+impl From<Level> for WlvBeta {
+    fn from(other: Level) -> Self {
+        other.downgrade().0
+    }
+}
+
+impl Level {
+    /// Drops tile logic, object fields the beta doesn't have and every level setting but the water's.
+    /// The report lists whatever converting back with the default options wouldn't restore.
+    pub fn downgrade(self) -> (WlvBeta, DowngradeReport) {
+        let mut report = DowngradeReport::default();
+        let width = self.terrain.width();
+        let terrain = self.terrain.map_level_tiles(|i, tile| {
+            if tile.logic() != LevelTileLogic::from_extrusion(tile.beta().extrusion()) {
+                report.losses.push(DowngradeLoss::TileLogic { x: i as i32 % width, y: i as i32 / width, logic: tile.logic() });
+            }
+            *tile.beta()
+        });
+
+        let defaults = ConversionOptions::default();
+        let format_color = |color: ColorRgb<i32>| format!("({}, {}, {})", color.red, color.green, color.blue);
+        let settings = [
+            ("level texture", self.level_texture_name.name().to_owned(), defaults.level_texture.name().to_owned()),
+            ("water texture", self.water_texture_name.name().to_owned(), defaults.water_texture.name().to_owned()),
+            ("edge style", self.edge_style.0.to_string(), defaults.edge_style.0.to_string()),
+            ("light", format_color(self.light), format_color(ColorRgb::DEFAULT_LIGHT)),
+            ("ambient", format_color(self.ambient), format_color(ColorRgb::DEFAULT_AMBIENT)),
+            ("music", self.music.0.to_string(), defaults.music.0.to_string()),
+            ("weather", self.weather.0.to_string(), defaults.weather.0.to_string()),
+            ("adventure title", format!("{:?}", self.adventure_title), format!("{:?}", defaults.adventure_title)),
+        ];
+        for (name, value, default) in settings {
+            if value != default {
+                report.losses.push(DowngradeLoss::Setting { name, value });
+            }
+        }
+        if let Some(open_wa) = &self.open_wa {
+            report.losses.push(DowngradeLoss::Setting { name: "OpenWA extension", value: format!("{:?}", open_wa) });
         }
+
+        let objects = self.objects.0.into_iter().enumerate().map(|(index, object)| {
+            report.add_object_losses(index, &object);
+            object.game_object.into()
+        }).collect();
+
+        let beta = WlvBeta {
+            terrain,
+            water_flow: self.water_flow,
+            water_transparent: self.water_transparent,
+            water_glow: self.water_glow,
+            objects: VecLen(objects),
+            save_extras: None,
+        };
+        (beta, report)
     }
 }
 
 pub fn convert_beta_to_wa3() -> Result<(), Error> {
-    let output_folder = "WA-BETA-MODERNIZED";
+    let output_folder = Path::new("WA-BETA-MODERNIZED");
     std::fs::create_dir_all(output_folder).map_err(Error::InputOutput)?;
     for entry in std::fs::read_dir("WA-BETA").map_err(Error::InputOutput)? {
         let entry = entry.map_err(Error::InputOutput)?;
-        let filename = entry.file_name();
-        let filename_str = filename.to_str().expect("filename should be valid UTF-8");
-        match entry
-            .path()
-            .extension()
-            .expect("extension should exist")
-            .to_str()
-            .expect("extension should be valid UTF-8") {
-            "wlv" => {
+        match entry.path().extension().and_then(|extension| extension.to_str()) {
+            Some("wlv") => {
                 println!("Converting {:?}", entry.path());
                 let wlv = WlvBeta::from_file(entry.path())?;
                 println!("Deserialization OK.");
-                wlv.modernize_to_file(&format!("{output_folder}/{filename_str}"))?;
+                wlv.modernize_to_file(output_folder.join(entry.file_name()))?;
                 println!("Serialization OK.");
             },
             other => {
-                println!("Ignoring unsupported file extension {other}");
+                println!("Ignoring {:?} with unsupported file extension {:?}", entry.path(), other);
             }
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_round_trip() {
+        let mut level = Level::new(3, 2);
+        level.add_object(GameObject::new(String::from("!Stinker"), String::from("!Stinker"), ObjectLogic::NONE).into());
+        level.add_object(GameObject::new(String::from("!Chomper"), String::from("!Chomper"), ObjectLogic::NONE).into());
+        level.set_water_glow(true);
+        level.set_adventure_title(String::from("Test"));
+
+        let mut bytes = vec![];
+        serde_blitz3d::to_writer(&mut bytes, &level).unwrap();
+        let read: Level = serde_blitz3d::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(read.terrain(), level.terrain());
+        assert_eq!(read.objects().len(), 2);
+        assert!(read.water_glow());
+        assert_eq!(read.adventure_title(), "Test");
+    }
+
+    #[test]
+    fn test_open_wa_extension() {
+        let mut level = Level::new(1, 1);
+        let vanilla = level.to_bytes(WlvFormat::Vanilla).unwrap();
+        assert!(Level::from_bytes(&vanilla).unwrap().open_wa().is_none());
+
+        level.set_open_wa(Some(OpenWaExtension::new(3)));
+        let mut open_wa = level.to_bytes(WlvFormat::OpenWa).unwrap();
+        assert_eq!(open_wa.len(), vanilla.len() + 8);
+        // A field from some future version of OpenWA.
+        open_wa.extend_from_slice(&7i32.to_le_bytes());
+        let read = Level::from_bytes(&open_wa).unwrap();
+        let extension = read.open_wa().unwrap();
+        assert_eq!(extension.widescreen_range_level, 3);
+        assert_eq!(extension.unknown(), 7i32.to_le_bytes());
+        assert_eq!(read.to_bytes(WlvFormat::OpenWa).unwrap(), open_wa);
+        assert_eq!(read.to_bytes(WlvFormat::Vanilla).unwrap(), vanilla);
+
+        assert!(Level::from_bytes(&[vanilla.as_slice(), &5i32.to_le_bytes()].concat()).is_err());
+    }
+
+    #[test]
+    fn test_beta_save_extras() {
+        let mut beta = WlvBeta::new(2, 1);
+        let plain = beta.to_bytes().unwrap();
+        let light_goals = LightGoals {
+            light: LightGoal { goal: ColorRgb::splat(200), change_speed: 1 },
+            ambient: LightGoal { goal: ColorRgb::splat(50), change_speed: 1 },
+        };
+        beta.set_save_extras(Some(BetaSaveExtras {
+            level_tile_logics: vec![LevelTileLogic::WATER, LevelTileLogic::LAVA],
+            light_goals,
+        }));
+        let save = beta.to_bytes().unwrap();
+        assert_eq!(save.len(), plain.len() + 2 * 4 + 8 * 4);
+
+        let read = WlvBeta::from_bytes(&save).unwrap();
+        assert_eq!(read.save_extras(), beta.save_extras());
+        let level: Level = read.into();
+        assert_eq!(level.light(), ColorRgb::splat(200));
+        assert_eq!(level.ambient(), ColorRgb::splat(50));
+
+        assert!(WlvBeta::from_bytes(&save[..save.len() - 4]).is_err());
+    }
+}
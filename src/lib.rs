@@ -1,3 +1,6 @@
+pub mod batch;
+pub use batch::*;
+pub mod cli;
 pub mod cmd;
 pub use cmd::*;
 pub mod dialog;
@@ -8,8 +11,14 @@ pub mod light;
 pub use light::*;
 pub mod object;
 pub use object::*;
+pub mod player;
+pub use player::*;
 pub mod primitives;
 pub use primitives::*;
+pub mod random;
 pub mod serde_blitz3d;
+pub mod text;
+pub use text::*;
 pub mod tile;
-pub use tile::*;
\ No newline at end of file
+pub use tile::*;
+pub mod time;
\ No newline at end of file
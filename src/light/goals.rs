@@ -1,13 +1,13 @@
 use crate::ColorRgb;
 use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct LightGoal {
     pub goal: ColorRgb<i32>,
     pub change_speed: i32,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct LightGoals {
     pub light: LightGoal,
     pub ambient: LightGoal,
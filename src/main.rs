@@ -1,5 +1,6 @@
+use std::process::ExitCode;
 use waust::*;
 
-fn main() {
-    convert_beta_to_wa3().unwrap();
-}
\ No newline at end of file
+fn main() -> ExitCode {
+    cli::run(std::env::args().skip(1))
+}
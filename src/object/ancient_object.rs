@@ -2,7 +2,7 @@ use crate::*;
 use serde::{Serialize, Deserialize};
 
 /// A set of common object adjusters that have been present since the beta.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct AncientObject {
     model_name: String,
     texture_name: String,
@@ -94,7 +94,18 @@ impl AncientObject {
         }
     }
 
-    
+    pub fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    pub fn texture_name(&self) -> &str {
+        &self.texture_name
+    }
+
+    pub fn logic(&self) -> ObjectLogic {
+        self.logic
+    }
+
     pub fn set_logic(&mut self, logic: ObjectLogic) {
         self.logic = logic;
     }
@@ -111,14 +122,67 @@ impl AncientObject {
         self.last_active = last_active;
     }
 
+    pub fn world_pos(&self) -> Vector3<f32> {
+        self.world_pos
+    }
+
     pub fn set_world_pos(&mut self, pos: Vector3<f32>) {
         self.world_pos = pos;
     }
 
+    /// All -999 until the object has moved.
+    pub fn old_world_pos(&self) -> Vector3<f32> {
+        self.old_world_pos
+    }
+
+    pub fn set_old_world_pos(&mut self, pos: Vector3<f32>) {
+        self.old_world_pos = pos;
+    }
+
+    pub fn delta(&self) -> Vector3<f32> {
+        self.delta
+    }
+
     pub fn set_delta(&mut self, delta: Vector3<f32>) {
         self.delta = delta;
     }
 
+    pub fn rotation(&self) -> EulerAngles {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: EulerAngles) {
+        self.rotation = rotation;
+    }
+
+    pub fn rotation2(&self) -> EulerAngles {
+        self.rotation2
+    }
+
+    pub fn set_rotation2(&mut self, rotation2: EulerAngles) {
+        self.rotation2 = rotation2;
+    }
+
+    pub fn goal(&self) -> Vector3<f32> {
+        self.goal
+    }
+
+    pub fn set_goal(&mut self, goal: Vector3<f32>) {
+        self.goal = goal;
+    }
+
+    pub fn push_delta(&self) -> Vector2<f32> {
+        self.push_delta
+    }
+
+    pub fn set_push_delta(&mut self, push_delta: Vector2<f32>) {
+        self.push_delta = push_delta;
+    }
+
+    pub fn movement_type(&self) -> MovementType {
+        self.movement_type
+    }
+
     pub fn set_timer(&mut self, timer: i32) {
         self.timer = timer;
     }
@@ -133,10 +197,39 @@ impl AncientObject {
         }
     }
 
+    pub fn data(&self, index: usize) -> i32 {
+        self.data[index]
+    }
+
     pub fn set_data(&mut self, index: usize, value: i32) {
         self.data[index] = value;
     }
 
+    /// Shared by every object that should respond to the same activation. -1 if none.
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    pub fn set_id(&mut self, id: i32) {
+        self.id = id;
+    }
+
+    pub fn parent(&self) -> i32 {
+        self.parent
+    }
+
+    pub fn set_parent(&mut self, parent: i32) {
+        self.parent = parent;
+    }
+
+    pub fn child(&self) -> i32 {
+        self.child
+    }
+
+    pub fn set_child(&mut self, child: i32) {
+        self.child = child;
+    }
+
     pub fn set_movement_type(&mut self, value: MovementType) {
         self.movement_type = value;
     }
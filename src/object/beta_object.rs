@@ -1,7 +1,7 @@
 use crate::*;
 use serde::{Serialize, Deserialize};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BetaObject {
     ancient_object: AncientObject,
     future_int_1: i32,
@@ -18,6 +18,38 @@ pub struct BetaObject {
     future_string_2: String,
 }
 
+impl BetaObject {
+    /// The future fields are left empty, as the beta never used them.
+    pub fn new(ancient_object: AncientObject) -> Self {
+        Self {
+            ancient_object,
+            future_int_1: 0,
+            future_int_2: 0,
+            future_int_3: 0,
+            future_int_4: 0,
+            future_int_5: 0,
+            future_float_1: 0.0,
+            future_float_2: 0.0,
+            future_float_3: 0.0,
+            future_float_4: 0.0,
+            future_float_5: 0.0,
+            future_string_1: String::new(),
+            future_string_2: String::new(),
+        }
+    }
+
+    pub fn ancient_object(&self) -> &AncientObject {
+        &self.ancient_object
+    }
+}
+
+/// Drops everything that isn't in the ancient object.
+impl From<GameObject> for BetaObject {
+    fn from(value: GameObject) -> Self {
+        Self::new(value.into_ancient_object())
+    }
+}
+
 impl From<BetaObject> for GameObject {
     fn from(value: BetaObject) -> Self {
         Self::from_ancient_object(value.ancient_object)
@@ -7,7 +7,7 @@ use std::io::SeekFrom;
 use std::path::Path;
 
 /// An object that exists during gameplay.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GameObject {
     ancient_object: AncientObject,
     talkable: i32,
@@ -91,6 +91,30 @@ impl GameObject {
         }
     }
 
+    pub fn ancient_object(&self) -> &AncientObject {
+        &self.ancient_object
+    }
+
+    pub fn ancient_object_mut(&mut self) -> &mut AncientObject {
+        &mut self.ancient_object
+    }
+
+    pub fn into_ancient_object(self) -> AncientObject {
+        self.ancient_object
+    }
+
+    pub fn model_name(&self) -> &str {
+        self.ancient_object.model_name()
+    }
+
+    pub fn texture_name(&self) -> &str {
+        self.ancient_object.texture_name()
+    }
+
+    pub fn logic(&self) -> ObjectLogic {
+        self.ancient_object.logic()
+    }
+
     pub fn set_logic(&mut self, logic: ObjectLogic) {
         self.ancient_object.set_logic(logic);
     }
@@ -119,10 +143,30 @@ impl GameObject {
         self.scale_adjust = scale_adjust;
     }
 
+    pub fn tile_pos(&self) -> Vector2<i32> {
+        self.tile_pos
+    }
+
     pub fn set_tile_pos(&mut self, pos: Vector2<i32>) {
         self.tile_pos = pos;
     }
 
+    pub fn tile_pos2(&self) -> Vector2<i32> {
+        self.tile_pos2
+    }
+
+    pub fn set_tile_pos2(&mut self, pos: Vector2<i32>) {
+        self.tile_pos2 = pos;
+    }
+
+    pub fn move_goal(&self) -> Vector2<i32> {
+        self.move_goal
+    }
+
+    pub fn set_move_goal(&mut self, move_goal: Vector2<i32>) {
+        self.move_goal = move_goal;
+    }
+
     pub fn set_world_pos(&mut self, pos: Vector3<f32>) {
         self.ancient_object.set_world_pos(pos);
     }
@@ -155,15 +199,51 @@ impl GameObject {
         self.movement_speed = value;
     }
 
+    pub fn linked(&self) -> i32 {
+        self.linked
+    }
+
+    pub fn set_linked(&mut self, linked: i32) {
+        self.linked = linked;
+    }
+
+    pub fn link_back(&self) -> i32 {
+        self.link_back
+    }
+
+    pub fn set_link_back(&mut self, link_back: i32) {
+        self.link_back = link_back;
+    }
+
+    /// The Cmd a command button runs. None for every other kind of object.
+    pub fn command(&self) -> Option<Cmd> {
+        let data = |i| self.ancient_object.data(i);
+        (self.logic() == ObjectLogic::COMMAND_BUTTON).then(|| Cmd::new(data(0), data(1), data(2), data(3), data(4)))
+    }
+
+    /// Does nothing unless this is a command button.
+    pub fn set_command(&mut self, cmd: Cmd) {
+        if self.logic() == ObjectLogic::COMMAND_BUTTON {
+            for (i, value) in [cmd.id, cmd.data1, cmd.data2, cmd.data3, cmd.data4].into_iter().enumerate() {
+                self.set_data(i, value);
+            }
+        }
+    }
+
     pub fn set_button_push(&mut self, value: i32) {
         self.ancient_object.set_button_push(value);
     }
 
-    pub fn to_wop(&self, filename: &str) -> Result<(), Error> {
-        let file = File::create(format!("{}.wop", filename)).map_err(Error::InputOutput)?;
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let file = File::create(path).map_err(Error::InputOutput)?;
         serde_blitz3d::to_writer(file, self).map_err(Error::Serde)
     }
 
+    #[deprecated(note = "adds \".wop\" to the file name; use to_file with the whole path instead")]
+    pub fn to_wop(&self, filename: &str) -> Result<(), Error> {
+        self.to_file(format!("{}.wop", filename))
+    }
+
     pub fn from_wop<P: AsRef<Path>>(path: P) -> Result<GameObject, Error> {
         let file = File::open(path).map_err(Error::InputOutput)?;
         serde_blitz3d::from_reader(file).map_err(Error::Serde)
@@ -176,6 +256,84 @@ impl GameObject {
         file.read_to_end(&mut buffer).unwrap();
         serde_blitz3d::from_reader(&buffer[..]).map_err(Error::Serde)
     }
+
+    /// Every field the ancient object doesn't have, by name, with its value in debug form.
+    /// Listing them by destructuring means a new field can't be forgotten here.
+    pub(crate) fn latest_only_fields(&self) -> [(&'static str, String); 34] {
+        let Self {
+            ancient_object: _,
+            talkable,
+            current_anim,
+            standard_anim,
+            tile_pos,
+            tile_pos2,
+            movement_timer,
+            movement_speed,
+            move_goal,
+            tile_type_collision,
+            object_type_collision,
+            caged,
+            dead,
+            dead_timer,
+            exclamation,
+            shadow,
+            linked,
+            link_back,
+            flying,
+            frozen,
+            indigo,
+            future_int24,
+            future_int25,
+            scale_adjust,
+            scale_x_adjust,
+            scale_y_adjust,
+            scale_z_adjust,
+            future_float5,
+            future_float6,
+            future_float7,
+            future_float8,
+            future_float9,
+            future_float10,
+            future_string_1,
+            future_string_2,
+        } = self;
+        [
+            ("talkable", format!("{:?}", talkable)),
+            ("current_anim", format!("{:?}", current_anim)),
+            ("standard_anim", format!("{:?}", standard_anim)),
+            ("tile_pos", format!("{:?}", tile_pos)),
+            ("tile_pos2", format!("{:?}", tile_pos2)),
+            ("movement_timer", format!("{:?}", movement_timer)),
+            ("movement_speed", format!("{:?}", movement_speed)),
+            ("move_goal", format!("{:?}", move_goal)),
+            ("tile_type_collision", format!("{:?}", tile_type_collision)),
+            ("object_type_collision", format!("{:?}", object_type_collision)),
+            ("caged", format!("{:?}", caged)),
+            ("dead", format!("{:?}", dead)),
+            ("dead_timer", format!("{:?}", dead_timer)),
+            ("exclamation", format!("{:?}", exclamation)),
+            ("shadow", format!("{:?}", shadow)),
+            ("linked", format!("{:?}", linked)),
+            ("link_back", format!("{:?}", link_back)),
+            ("flying", format!("{:?}", flying)),
+            ("frozen", format!("{:?}", frozen)),
+            ("indigo", format!("{:?}", indigo)),
+            ("future_int24", format!("{:?}", future_int24)),
+            ("future_int25", format!("{:?}", future_int25)),
+            ("scale_adjust", format!("{:?}", scale_adjust)),
+            ("scale_x_adjust", format!("{:?}", scale_x_adjust)),
+            ("scale_y_adjust", format!("{:?}", scale_y_adjust)),
+            ("scale_z_adjust", format!("{:?}", scale_z_adjust)),
+            ("future_float5", format!("{:?}", future_float5)),
+            ("future_float6", format!("{:?}", future_float6)),
+            ("future_float7", format!("{:?}", future_float7)),
+            ("future_float8", format!("{:?}", future_float8)),
+            ("future_float9", format!("{:?}", future_float9)),
+            ("future_float10", format!("{:?}", future_float10)),
+            ("future_string_1", format!("{:?}", future_string_1)),
+            ("future_string_2", format!("{:?}", future_string_2)),
+        ]
+    }
 }
 
 #[derive(Debug)]
@@ -1,6 +1,7 @@
+use crate::*;
 use serde::*;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub struct MovementType(i32);
 
 impl MovementType {
@@ -13,4 +14,30 @@ impl MovementType {
     pub const SOUTH_RIGHT: Self = Self(46);
     pub const WEST_LEFT: Self = Self(47);
     pub const WEST_RIGHT: Self = Self(48);
-}
\ No newline at end of file
+
+    // Directions go clockwise from north, each with a left and a right variant.
+    fn direction(self) -> Option<(i32, i32)> {
+        (Self::NORTH_LEFT.0..=Self::WEST_RIGHT.0).contains(&self.0).then(|| ((self.0 - 41) / 2, (self.0 - 41) % 2))
+    }
+
+    fn from_direction(direction: i32, hand: i32) -> Self {
+        Self(41 + direction.rem_euclid(4) * 2 + hand)
+    }
+
+    /// Turns a directional movement type along with a level rotated a quarter turn clockwise.
+    pub fn rotated_clockwise(self) -> Self {
+        match self.direction() {
+            Some((direction, hand)) => Self::from_direction(direction + 1, hand),
+            None => self
+        }
+    }
+
+    /// Mirroring swaps left and right as well as the mirrored directions.
+    pub fn mirrored(self, mirror: Mirror) -> Self {
+        match (self.direction(), mirror) {
+            (Some((direction, hand)), Mirror::LeftRight) => Self::from_direction(-direction, 1 - hand),
+            (Some((direction, hand)), Mirror::TopBottom) => Self::from_direction(2 - direction, 1 - hand),
+            (None, _) => self
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use serde::*;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ObjectLogic {
     object_type: i32,
     object_subtype: i32
@@ -8,6 +8,10 @@ pub struct ObjectLogic {
 
 impl ObjectLogic {
     pub const NONE: Self = Self::new(0, 0);
+    /// Where the player starts the level.
+    pub const PLAYER: Self = Self::new(1, 0);
+    /// A button that runs the Cmd stored in its data 0 through 4 when pressed.
+    pub const COMMAND_BUTTON: Self = Self::new(45, 10);
 
     pub const fn new(object_type: i32, object_subtype: i32) -> ObjectLogic {
         Self {
@@ -16,6 +20,14 @@ impl ObjectLogic {
         }
     }
 
+    pub fn object_type(&self) -> i32 {
+        self.object_type
+    }
+
+    pub fn object_subtype(&self) -> i32 {
+        self.object_subtype
+    }
+
     pub fn spellball(color: i32) -> ObjectLogic {
         Self::new(50, color)
     }
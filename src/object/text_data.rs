@@ -1,6 +1,6 @@
 use serde::*;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TextData {
     text_data: [String; 4]
 }
@@ -2,7 +2,7 @@ use crate::*;
 use serde::{Serialize, Deserialize};
 
 /// An object saved to a WLV.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct WlvObject {
     pub game_object: GameObject,
     pub adjusters: [String; 30],
@@ -1,4 +1,9 @@
-#[derive(Debug, Serializable, Deserializable)]
+use std::{fs, path::Path};
+
+use crate::*;
+use serde::{Serialize, Deserialize};
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct HubStatus {
     in_custom_hub: bool,
     in_custom_hub_name: String,
@@ -7,7 +12,7 @@ pub struct HubStatus {
     gate_key_version: i32
 }
 
-#[derive(Debug, Serializable, Deserializable)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Player {
     name: String,
     character_name: String,
@@ -16,12 +21,12 @@ pub struct Player {
     accessory1_texture: i32,
     accessory2: i32,
     accessory2_texture: i32,
-    size: Scale<f32, 3>,
+    size: Vector3<f32>,
     voice: i32,
     pitch: i32
 }
 
-#[derive(Debug, Serializable, Deserializable)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct InventoryItem {
     item: i32,
     id: i32,
@@ -30,10 +35,11 @@ pub struct InventoryItem {
     help_text: String
 }
 
-#[derive(Debug, Serializable, Deserializable)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Inventory {
     size: i32,
     number_of_inventory_items: i32,
+    #[serde(with = "serde_blitz3d::fixed_array")]
     items: [InventoryItem; 100],
     coins: i32,
     coins_collected: i32,
@@ -43,57 +49,49 @@ pub struct Inventory {
 }
 
 impl Inventory {
-    fn decrypt_field(arg: i32) -> i32 {
-        todo!()
+    fn encrypt_field(value: i32) -> i32 {
+        (value + 3).pow(2) * 5 + 11
     }
 
-    fn decrypt_field(arg: i32) -> i32 {
-        ((arg - 11) / 5).sqrt() - 3
+    fn decrypt_field(value: i32) -> i32 {
+        ((value - 11) / 5).isqrt() - 3
     }
 
     pub fn encrypt(&mut self) {
-        todo!()
+        self.coins = Self::encrypt_field(self.coins);
+        self.stars = Self::encrypt_field(self.stars);
     }
 
     pub fn decrypt(&mut self) {
-        self.coins = self.decrypt_field(self.coins);
-        self.stars = self.decrypt_field(self.stars);
+        self.coins = Self::decrypt_field(self.coins);
+        self.stars = Self::decrypt_field(self.stars);
     }
 }
 
-#[derive(Debug, Serializable, Deserializable)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct Widescreen {
     fit_for_widescreen_global: i32,
     fit_for_widescreen_global_hub: i32,
     future: i32
 }
 
-#[derive(Debug, Serializable, Deserializable)]
-pub struct Command {
-    id: i32,
-    data1: i32,
-    data2: i32,
-    data3: i32,
-    data4: i32
-}
-
-#[derive(Debug, Serializable, Deserializable)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct AdventureWonCommand {
     level: i32,
-    command: Command
+    command: Cmd
 }
 
-#[derive(Debug, Serializable, Deserializable)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct AdventureExit {
     won_level: i32,
-    won_pos: Pos<i32, 2>,
+    won_pos: Vector2<i32>,
     lost_level: i32,
-    lost_pos: Pos<i32, 2>,
+    lost_pos: Vector2<i32>,
     goal: i32,
     won_commands: [AdventureWonCommand; 3]
 }
 
-#[derive(Debug, Serializable, Deserializable)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct CurrentAdventure {
     gems: i32,
     coins: i32,
@@ -107,7 +105,7 @@ pub struct CurrentAdventure {
     exit: AdventureExit,
 }
 
-#[derive(Debug, Serializable, Deserializable)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct AdventureCompletion {
     completed: i32,
     time: i32,
@@ -118,7 +116,7 @@ pub struct AdventureCompletion {
     score: i32
 }
 
-#[derive(Debug, Serializable, Deserializable)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct GlobalData {
     player_object: i32,
     stinker_object: i32,
@@ -131,7 +129,7 @@ pub struct GlobalData {
     old_game_mode: i32,
     move_cursor_new_target: i32,
     mouse_held: i32,
-    delay_command: Command,
+    delay_command: Cmd,
     spell_active: i32,
     current_spell: i32,
     current_charm: i32,
@@ -148,7 +146,7 @@ pub struct GlobalData {
     custom_map_name: String
 }
 
-#[derive(Debug, Serializable, Deserializable)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct CurrentAdventureCounts {
     wee_stinkers: i32,
     wee_stinkers_following: i32,
@@ -163,20 +161,20 @@ pub struct CurrentAdventureCounts {
     future: [i32; 7]
 }
 
-#[derive(Debug, Serializable, Deserializable)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct ReplayAdventure {
     current: i32,
     pre_level: i32,
-    pre_pos: Pos<i32, 2>
+    pre_pos: Vector2<i32>
 }
 
-#[derive(Debug, Serializable, Deserializable)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct LevelMusic {
     volume: i32,
     pitch: i32
 }
 
-#[derive(Debug, Serializable, Deserializable)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct PlayerSave {
     hub_status: HubStatus,
     player: Player,
@@ -186,12 +184,14 @@ pub struct PlayerSave {
     inventory_b4: Inventory,
     light_power_b4: i32,
     current_adventure: CurrentAdventure,
+    #[serde(with = "serde_blitz3d::fixed_array")]
     adventure_completions: [AdventureCompletion; 500],
     counts: CurrentAdventureCounts,
     global_data: GlobalData,
     future_strings: [String; 4],
     future_floats: [f32; 10],
-    master_askabout_active: [bool; 1000],
+    #[serde(with = "serde_blitz3d::fixed_array")]
+    master_askabout_active: [bool; AskAboutActive::MASTER_COUNT],
     map_piece_found: [bool; 8],
     mystery_number: [i32; 4],
     mystery_number_pos: i32,
@@ -202,16 +202,24 @@ pub struct PlayerSave {
     // TODO: more
 }
 
-impl Player {
-    pub fn create_player_dirs() {
-        todo!();
+impl PlayerSave {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_bytes(&fs::read(path).map_err(Error::InputOutput)?)
     }
 
-    pub fn new(player_name: String) {
+    /// Only reads as far as the fields known so far, ignoring the rest of the save.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        serde_blitz3d::from_reader(bytes).map_err(Error::Serde)
+    }
 
+    /// The askabout states that askabouts can share across the adventure, indexed by `AskAboutActive::master_index`.
+    pub fn master_askabout_active(&self) -> &[bool; AskAboutActive::MASTER_COUNT] {
+        &self.master_askabout_active
     }
-}
 
-pub fn create_new_player(player_name: &str) {
-    
-}
\ No newline at end of file
+    /// Returns what the entry was before, or None if there's no such entry.
+    pub fn set_master_askabout_active(&mut self, index: usize, active: bool) -> Option<bool> {
+        let entry = self.master_askabout_active.get_mut(index)?;
+        Some(std::mem::replace(entry, active))
+    }
+}
@@ -1,8 +1,19 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+/// In degrees, as Blitz3D uses them. Positive yaw turns left, seen from above.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
 pub struct EulerAngles {
-    pitch: f32,
-    yaw: f32,
-    roll: f32
+    pub pitch: f32,
+    pub yaw: f32,
+    pub roll: f32
+}
+
+impl EulerAngles {
+    pub fn new(pitch: f32, yaw: f32, roll: f32) -> Self {
+        Self {
+            pitch,
+            yaw,
+            roll
+        }
+    }
 }
\ No newline at end of file
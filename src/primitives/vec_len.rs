@@ -1,11 +1,39 @@
 use std::marker::PhantomData;
 
-use serde::{de::{self, SeqAccess, Visitor}, Deserialize, Deserializer, Serialize};
+use crate::serde_blitz3d;
+use serde::{de::{self, SeqAccess, Visitor}, ser::SerializeTuple, Deserialize, Deserializer, Serialize, Serializer};
 
 /// A Vec that gets serialized in a special way by serde.
-#[derive(Debug, Serialize)]
+/// Its length is written before its elements, except in human-readable formats where it's a plain list.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VecLen<T>(pub Vec<T>);
 
+impl<T> Default for VecLen<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T> Serialize for VecLen<T>
+where
+    T: Serialize
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        if serializer.is_human_readable() {
+            return self.0.serialize(serializer);
+        }
+        let mut tuple = serializer.serialize_tuple(self.0.len() + 1)?;
+        tuple.serialize_element(&(self.0.len() as i32))?;
+        for element in &self.0 {
+            tuple.serialize_element(element)?;
+        }
+        tuple.end()
+    }
+}
+
 pub struct VecLenVisitor<T> {
     phantom: PhantomData<T>
 }
@@ -18,6 +46,9 @@ where
     where
         D: Deserializer<'de>
     {
+        if deserializer.is_human_readable() {
+            return Vec::deserialize(deserializer).map(VecLen);
+        }
         deserializer.deserialize_struct("VecLen", &["0"], VecLenVisitor::new())
     }
 }
@@ -45,8 +76,9 @@ where
         V: SeqAccess<'de>
     {
         let length: i32 = seq.next_element()?.ok_or_else(|| de::Error::custom("unexpected end of sequence"))?;
-        let mut vector = Vec::with_capacity(length as usize);
-        for _ in 0..length as usize {
+        let length = usize::try_from(length).map_err(|_| de::Error::custom(format!("negative length {}", length)))?;
+        let mut vector = Vec::with_capacity(length.min(serde_blitz3d::MAX_PREALLOCATION));
+        for _ in 0..length {
             let element = seq.next_element()?.ok_or_else(|| de::Error::custom("unexpected end of sequence"))?;
             vector.push(element);
         }
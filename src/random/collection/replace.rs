@@ -32,6 +32,18 @@ pub trait ReplaceSlice<T>
         N: Rng<u32>,
         E: ReplacementPattern<T>,
         F: FnMut(&mut N) -> T;
+
+    fn randomly_modify_target_count<N, E, F>(&mut self, rng: &mut N, target: E, modification: F, count: usize)
+    where
+        N: Rng<usize>,
+        E: ReplacementPattern<T>,
+        F: FnMut(&mut N, &mut T);
+
+    fn randomly_modify_target_chance<N, E, F>(&mut self, rng: &mut N, target: E, modification: F, chance: Chance)
+    where
+        N: Rng<u32>,
+        E: ReplacementPattern<T>,
+        F: FnMut(&mut N, &mut T);
 }
 
 impl<T> ReplaceSlice<T> for [T]
@@ -108,6 +120,31 @@ impl<T> ReplaceSlice<T> for [T]
             }
         }
     }
+
+    fn randomly_modify_target_count<N, E, F>(&mut self, rng: &mut N, target: E, mut modification: F, count: usize)
+    where
+        N: Rng<usize>,
+        E: ReplacementPattern<T>,
+        F: FnMut(&mut N, &mut T)
+    {
+        let targets = self.iter_mut().filter(|t| target.satisfied_by(t)).random_permutation(rng, count);
+        for t in targets {
+            modification(rng, t);
+        }
+    }
+
+    fn randomly_modify_target_chance<N, E, F>(&mut self, rng: &mut N, target: E, mut modification: F, chance: Chance)
+    where
+        N: Rng<u32>,
+        E: ReplacementPattern<T>,
+        F: FnMut(&mut N, &mut T)
+    {
+        for t in self.iter_mut().filter(|t| target.satisfied_by(t)) {
+            if chance.happens(rng) {
+                modification(rng, t);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -122,4 +159,39 @@ mod tests {
         array.replace_target(|num: &i32| *num > 3, || 0);
         assert_eq!(array, [1, 2, 0, 0, 0]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_modify_target_count() {
+        let mut rng = Pcg32::new(7, 0);
+        let mut array = [1, 2, 3, 4, 5, 6];
+        array.randomly_modify_target_count(&mut rng, |num: &i32| num % 2 == 0, |_, num| *num += 10, 2);
+        assert_eq!(array.iter().filter(|num| **num > 10).count(), 2);
+        assert!(array.iter().all(|num| *num <= 6 || num % 2 == 0));
+
+        // Asking for more than there are modifies all of them.
+        let mut array = [1, 2, 3, 4, 5, 6];
+        array.randomly_modify_target_count(&mut rng, |num: &i32| num % 2 == 0, |_, num| *num += 10, 10);
+        assert_eq!(array, [1, 12, 3, 14, 5, 16]);
+
+        let mut array = [1, 3, 5];
+        array.randomly_modify_target_count(&mut rng, |num: &i32| num % 2 == 0, |_, num| *num += 10, 2);
+        assert_eq!(array, [1, 3, 5]);
+    }
+
+    #[test]
+    fn test_modify_target_chance() {
+        let mut rng = Pcg32::new(7, 0);
+        let mut array = [0; 100];
+        array.randomly_modify_target_chance(&mut rng, 0, |_, num| *num = 1, Chance::new(1, 2));
+        let modified = array.iter().filter(|num| **num == 1).count();
+        assert!(modified > 0 && modified < 100, "{}", modified);
+
+        let mut array = [1, 2, 3];
+        array.randomly_modify_target_chance(&mut rng, 2, |_, num| *num = 0, Chance::new(1, 1));
+        assert_eq!(array, [1, 0, 3]);
+        array.randomly_modify_target_chance(&mut rng, 2, |_, num| *num = 0, Chance::new(1, 1));
+        assert_eq!(array, [1, 0, 3]);
+        array.randomly_modify_target_chance(&mut rng, 1, |_, num| *num = 0, Chance::new(0, 1));
+        assert_eq!(array, [1, 0, 3]);
+    }
+}
@@ -1,7 +1,9 @@
 mod de;
 mod error;
+pub mod fixed_array;
+pub mod human_readable_only;
 mod ser;
 
-pub use de::{from_reader, Deserializer};
+pub use de::{from_reader, Deserializer, MAX_PREALLOCATION};
 pub use error::{Error, Result};
 pub use ser::{to_writer, Serializer};
\ No newline at end of file
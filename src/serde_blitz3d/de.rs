@@ -7,6 +7,9 @@ use serde::de::{
 
 use super::error::{Error, Result};
 
+/// The most elements or bytes to reserve up front based on a length read from the input.
+pub const MAX_PREALLOCATION: usize = 1 << 16;
+
 pub struct Deserializer<R> where R: Read {
     reader: R,
 }
@@ -52,10 +55,15 @@ impl<R> Deserializer<R> where R: Read {
 
     // Requires the string to be valid UTF-8, which might be a bit too strict.
     fn parse_string(&mut self) -> Result<String> {
-        let length = self.parse_i32()? as usize;
-        let mut buf = String::with_capacity(length);
-        self.reader.by_ref().take(length as u64).read_to_string(&mut buf).map_err(Error::InputOutput)?;
-        Ok(buf)
+        let length = self.parse_i32()?;
+        let length = usize::try_from(length).map_err(|_| Error::Message(format!("negative string length {}", length)))?;
+        // The length might be garbage, so let the buffer grow as bytes actually arrive.
+        let mut buf = Vec::with_capacity(length.min(MAX_PREALLOCATION));
+        self.reader.by_ref().take(length as u64).read_to_end(&mut buf).map_err(Error::InputOutput)?;
+        if buf.len() < length {
+            return Err(Error::Message(format!("string of length {} ended after {} bytes", length, buf.len())));
+        }
+        String::from_utf8(buf).map_err(|e| Error::Message(e.to_string()))
     }
 }
 
@@ -65,6 +73,10 @@ where
 {
     type Error = Error;
 
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
     fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
@@ -0,0 +1,30 @@
+//! For `#[serde(default, with = "serde_blitz3d::human_readable_only")]` on optional fields
+//! that the Blitz3D format reads and writes separately, after everything else.
+//! The Blitz3D serializer writes nothing for them and the deserializer leaves them as None,
+//! while human-readable formats like JSON see them as ordinary fields.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer
+{
+    if serializer.is_human_readable() {
+        value.serialize(serializer)
+    } else {
+        serializer.serialize_unit()
+    }
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>
+{
+    if deserializer.is_human_readable() {
+        Option::deserialize(deserializer)
+    } else {
+        Ok(None)
+    }
+}
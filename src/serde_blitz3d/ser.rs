@@ -33,6 +33,11 @@ where
     type SerializeStruct = Self;
     type SerializeStructVariant = Self;
 
+    // Lets types write a compact layout here and a readable one in text formats.
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
     fn serialize_bool(self, v: bool) -> Result<()> {
         self.serialize_i32(i32::from(v))
     }
@@ -0,0 +1,153 @@
+use std::{fmt, fs, path::Path};
+
+use crate::*;
+use serde::{Serialize, Deserialize};
+
+/// Self-describing text formats that every file type can be written to for editing and read back from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextFormat {
+    Json,
+    Ron,
+}
+
+impl TextFormat {
+    /// Going by extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "json" => Some(TextFormat::Json),
+            "ron" => Some(TextFormat::Ron),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for TextFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextFormat::Json => write!(f, "JSON"),
+            TextFormat::Ron => write!(f, "RON")
+        }
+    }
+}
+
+/// The contents of a `.wlv`, `.dia` or `.wop`, tagged with what it is
+/// so that its text can be turned back into the right kind of file.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum TextDocument {
+    Level(Level),
+    BetaLevel(WlvBeta),
+    Dialog(Dialog),
+    Object(Box<GameObject>),
+}
+
+impl TextDocument {
+    pub fn from_binary_file<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let kind = FileKind::from_path(path).ok_or_else(|| malformed("not a .wlv, .dia or .wop file".to_owned()))?;
+        Self::from_binary(kind, &fs::read(path).map_err(Error::InputOutput)?)
+    }
+
+    /// Levels can be of any known version.
+    pub fn from_binary(kind: FileKind, bytes: &[u8]) -> Result<Self, Error> {
+        Ok(match kind {
+            FileKind::Level => match AnyWlv::from_bytes(bytes)?.0 {
+                AnyWlv::Beta(beta) => TextDocument::BetaLevel(beta),
+                AnyWlv::Latest(level) => TextDocument::Level(level)
+            },
+            FileKind::Dialog => TextDocument::Dialog(serde_blitz3d::from_reader(bytes).map_err(Error::Serde)?),
+            FileKind::Object => TextDocument::Object(Box::new(serde_blitz3d::from_reader(bytes).map_err(Error::Serde)?))
+        })
+    }
+
+    /// Levels keep their OpenWA extension or beta save file extras if they have them.
+    pub fn to_binary(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+        match self {
+            TextDocument::Level(level) => {
+                let format = if level.open_wa().is_some() { WlvFormat::OpenWa } else { WlvFormat::Vanilla };
+                bytes = level.to_bytes(format)?;
+            },
+            TextDocument::BetaLevel(beta) => bytes = beta.to_bytes()?,
+            TextDocument::Dialog(dialog) => serde_blitz3d::to_writer(&mut bytes, dialog).map_err(Error::Serde)?,
+            TextDocument::Object(object) => serde_blitz3d::to_writer(&mut bytes, object).map_err(Error::Serde)?
+        }
+        Ok(bytes)
+    }
+
+    pub fn kind(&self) -> FileKind {
+        match self {
+            TextDocument::Level(_) | TextDocument::BetaLevel(_) => FileKind::Level,
+            TextDocument::Dialog(_) => FileKind::Dialog,
+            TextDocument::Object(_) => FileKind::Object
+        }
+    }
+
+    pub fn to_text(&self, format: TextFormat) -> Result<String, Error> {
+        match format {
+            TextFormat::Json => serde_json::to_string_pretty(self).map_err(|error| malformed(error.to_string())),
+            TextFormat::Ron => ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|error| malformed(error.to_string()))
+        }
+    }
+
+    /// Also checks what the binary format leaves implicit, like the number of save file tile logics.
+    pub fn from_text(text: &str, format: TextFormat) -> Result<Self, Error> {
+        let document: Self = match format {
+            TextFormat::Json => serde_json::from_str(text).map_err(|error| malformed(error.to_string()))?,
+            TextFormat::Ron => ron::from_str(text).map_err(|error| malformed(error.to_string()))?
+        };
+        if let TextDocument::BetaLevel(beta) = &document {
+            let tile_count = beta.terrain().width() as usize * beta.terrain().height() as usize;
+            if let Some(extras) = beta.save_extras().filter(|extras| extras.level_tile_logics.len() != tile_count) {
+                return Err(malformed(format!("expected {} level tile logics, one per tile, but found {}", tile_count, extras.level_tile_logics.len())));
+            }
+        }
+        Ok(document)
+    }
+}
+
+fn malformed(message: String) -> Error {
+    Error::Serde(serde_blitz3d::Error::Message(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_round_trips(document: &TextDocument) {
+        let bytes = document.to_binary().unwrap();
+        for format in [TextFormat::Json, TextFormat::Ron] {
+            let text = document.to_text(format).unwrap();
+            let parsed = TextDocument::from_text(&text, format).unwrap();
+            assert_eq!(parsed.to_binary().unwrap(), bytes, "{} round trip", format);
+        }
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let mut level = Level::new(3, 2);
+        level.set_adventure_title("Title".to_owned());
+        level.add_object(GameObject::new("!Key".to_owned(), "".to_owned(), ObjectLogic::new(1, 0)).into());
+        level.set_open_wa(Some(OpenWaExtension::new(2)));
+        assert_round_trips(&TextDocument::Level(level));
+
+        let mut beta = WlvBeta::new(2, 2);
+        beta.set_save_extras(Some(BetaSaveExtras {
+            level_tile_logics: vec![LevelTileLogic::WATER; 4],
+            light_goals: LightGoals {
+                light: LightGoal { goal: ColorRgb::DEFAULT_LIGHT, change_speed: 1 },
+                ambient: LightGoal { goal: ColorRgb::DEFAULT_AMBIENT, change_speed: 2 },
+            },
+        }));
+        assert_round_trips(&TextDocument::BetaLevel(beta));
+
+        let mut dialog = Dialog::default();
+        dialog.add_interchange(Interchange::new(Body::plain_text(vec!["Hello".to_owned()]), vec![Reply::end(0, "Bye".to_owned(), Cmd::none())]));
+        let document = TextDocument::Dialog(dialog);
+        assert_round_trips(&document);
+        assert!(document.to_text(TextFormat::Json).unwrap().contains("\"text_lines\": [\n"));
+
+        let json = TextDocument::Level(Level::new(1, 1)).to_text(TextFormat::Json).unwrap();
+        assert!(TextDocument::from_text(&json.replacen("\"width\": 1", "\"width\": 2", 1), TextFormat::Json).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use serde::*;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub struct Rounding(i32);
 
 impl Rounding {
@@ -8,7 +8,7 @@ impl Rounding {
     pub const ROUND: Self = Self(1);
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub struct EdgeRandom(i32);
 
 impl EdgeRandom {
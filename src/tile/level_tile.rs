@@ -1,7 +1,33 @@
 use crate::*;
 use serde::*;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+/// What every version's level tiles have in common, so code can work on terrain from any version.
+pub trait LevelTile {
+    fn beta(&self) -> &LevelTileBeta;
+    fn beta_mut(&mut self) -> &mut LevelTileBeta;
+}
+
+impl LevelTile for LevelTileBeta {
+    fn beta(&self) -> &LevelTileBeta {
+        self
+    }
+
+    fn beta_mut(&mut self) -> &mut LevelTileBeta {
+        self
+    }
+}
+
+impl LevelTile for LevelTileLatest {
+    fn beta(&self) -> &LevelTileBeta {
+        &self.beta
+    }
+
+    fn beta_mut(&mut self) -> &mut LevelTileBeta {
+        &mut self.beta
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct LevelTileBeta {
     texture: i32,
     rotation: i32,
@@ -30,7 +56,81 @@ impl Default for LevelTileBeta {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+impl LevelTileBeta {
+    pub fn texture(&self) -> i32 {
+        self.texture
+    }
+
+    pub fn set_texture(&mut self, texture: i32) {
+        self.texture = texture;
+    }
+
+    pub fn side_texture(&self) -> i32 {
+        self.side_texture
+    }
+
+    pub fn set_side_texture(&mut self, side_texture: i32) {
+        self.side_texture = side_texture;
+    }
+
+    pub fn rotation(&self) -> i32 {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, rotation: i32) {
+        self.rotation = rotation;
+    }
+
+    pub fn side_rotation(&self) -> i32 {
+        self.side_rotation
+    }
+
+    pub fn set_side_rotation(&mut self, side_rotation: i32) {
+        self.side_rotation = side_rotation;
+    }
+
+    pub fn random(&self) -> f32 {
+        self.random
+    }
+
+    pub fn set_random(&mut self, random: f32) {
+        self.random = random;
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn set_height(&mut self, height: f32) {
+        self.height = height;
+    }
+
+    pub fn extrusion(&self) -> f32 {
+        self.extrusion
+    }
+
+    pub fn set_extrusion(&mut self, extrusion: f32) {
+        self.extrusion = extrusion;
+    }
+
+    pub fn rounding(&self) -> Rounding {
+        self.rounding
+    }
+
+    pub fn set_rounding(&mut self, rounding: Rounding) {
+        self.rounding = rounding;
+    }
+
+    pub fn edge_random(&self) -> EdgeRandom {
+        self.edge_random
+    }
+
+    pub fn set_edge_random(&mut self, edge_random: EdgeRandom) {
+        self.edge_random = edge_random;
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct LevelTileLatest {
     beta: LevelTileBeta,
     logic: LevelTileLogic,
@@ -45,18 +145,25 @@ impl Default for LevelTileLatest {
     }
 }
 
-impl From<LevelTileBeta> for LevelTileLatest {
-    fn from(beta: LevelTileBeta) -> Self {
-        let logic = if beta.extrusion == 0.0 {
-            LevelTileLogic::FLOOR
-        } else if beta.extrusion > 0.0 {
-            LevelTileLogic::WALL
-        } else {
-            LevelTileLogic::WATER
-        };
+impl LevelTileLatest {
+    pub fn new(beta: LevelTileBeta, logic: LevelTileLogic) -> Self {
         Self {
             beta,
             logic,
         }
     }
+
+    pub fn logic(&self) -> LevelTileLogic {
+        self.logic
+    }
+
+    pub fn set_logic(&mut self, logic: LevelTileLogic) {
+        self.logic = logic;
+    }
+}
+
+impl From<LevelTileBeta> for LevelTileLatest {
+    fn from(beta: LevelTileBeta) -> Self {
+        Self::new(beta, LevelTileLogic::from_extrusion(beta.extrusion))
+    }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use serde::*;
+use std::fmt;
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub struct LevelTileLogic(i32);
 
 impl LevelTileLogic {
@@ -19,9 +20,42 @@ impl LevelTileLogic {
     pub const ICE_CURVED: Self = Self(12);
     pub const ICE_WALL: Self = Self(13);
     pub const ICE_FLOAT: Self = Self(14);
+
+    /// Guesses the logic of a beta tile, which doesn't store any:
+    /// flat tiles are floor, raised tiles are walls and sunken tiles are water.
+    pub fn from_extrusion(extrusion: f32) -> Self {
+        if extrusion == 0.0 {
+            Self::FLOOR
+        } else if extrusion > 0.0 {
+            Self::WALL
+        } else {
+            Self::WATER
+        }
+    }
+}
+
+impl fmt::Display for LevelTileLogic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::FLOOR => write!(f, "floor"),
+            Self::WALL => write!(f, "wall"),
+            Self::WATER => write!(f, "water"),
+            Self::TELEPORTER => write!(f, "teleporter"),
+            Self::BRIDGE => write!(f, "bridge"),
+            Self::LAVA => write!(f, "lava"),
+            Self::CAGE => write!(f, "cage"),
+            Self::BUTTON => write!(f, "button"),
+            Self::STINKER_EXIT => write!(f, "stinker exit"),
+            Self::ICE_STRAIGHT => write!(f, "ice (straight)"),
+            Self::ICE_CURVED => write!(f, "ice (curved)"),
+            Self::ICE_WALL => write!(f, "ice wall"),
+            Self::ICE_FLOAT => write!(f, "ice float"),
+            Self(other) => write!(f, "logic {}", other)
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub struct ObjectTileLogic(i32);
 
 impl ObjectTileLogic {
@@ -37,10 +71,10 @@ impl ObjectTileLogic {
     pub const FROZEN: Self = Self(10);
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TileTypeCollision(i32);
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ObjectTypeCollision(i32);
 
 impl TileTypeCollision {